@@ -0,0 +1,163 @@
+use crate::{
+    arithmetic::{g_to_lagrange, same_ratio},
+    util::{
+        perpetual_powers_of_tau::{self, same_ratio_g2, same_ratio_pair, Accumulator},
+        snarkjs, Compression,
+    },
+    Srs, SrsError, SrsFormat,
+};
+use halo2_curves::{group::prime::PrimeCurveAffine, pairing::MultiMillerLoop};
+use std::io;
+
+/// All sections of a phase 1 powers of tau ceremony, which contains
+/// alpha_tau_g1, beta_tau_g1 and beta_g2 needed by Groth16 besides the powers
+/// of tau in [`Srs`].
+///
+/// Sections other than powers of tau are `None` if the source doesn't have
+/// them, which are written as identity by the writers of this crate.
+#[derive(Clone, Debug)]
+pub struct PhaseOneAccumulator<M: MultiMillerLoop> {
+    pub k: u32,
+    /// `2^(k+1) - 1` powers of tau in G1.
+    pub tau_g1: Vec<M::G1Affine>,
+    /// `2^k` powers of tau in G2, or 2 powers if `k` is 0, without trailing
+    /// ones written as identity by the writers of this crate, which only have
+    /// the first 2 powers from [`Srs`].
+    pub tau_g2: Vec<M::G2Affine>,
+    pub alpha_tau_g1: Option<Vec<M::G1Affine>>,
+    pub beta_tau_g1: Option<Vec<M::G1Affine>>,
+    pub beta_g2: Option<M::G2Affine>,
+}
+
+impl<M: MultiMillerLoop> PhaseOneAccumulator<M> {
+    pub fn read<R: io::Read + io::Seek>(reader: &mut R, format: SrsFormat) -> Self {
+        Self::try_read(reader, format).unwrap()
+    }
+
+    pub fn read_partial<R: io::Read + io::Seek>(
+        reader: &mut R,
+        format: SrsFormat,
+        desired_k: u32,
+    ) -> Self {
+        Self::try_read_partial(reader, format, desired_k).unwrap()
+    }
+
+    pub fn try_read<R: io::Read + io::Seek>(
+        reader: &mut R,
+        format: SrsFormat,
+    ) -> Result<Self, SrsError> {
+        Self::try_read_format(reader, format, None)
+    }
+
+    pub fn try_read_partial<R: io::Read + io::Seek>(
+        reader: &mut R,
+        format: SrsFormat,
+        desired_k: u32,
+    ) -> Result<Self, SrsError> {
+        Self::try_read_format(reader, format, Some(desired_k))
+    }
+
+    /// Reads from formats of Perpetual Powers of Tau and snarkjs, which are
+    /// the only ones containing all sections.
+    fn try_read_format<R: io::Read + io::Seek>(
+        reader: &mut R,
+        format: SrsFormat,
+        desired_k: Option<u32>,
+    ) -> Result<Self, SrsError> {
+        let (accumulator, desired_k) = match format {
+            SrsFormat::PerpetualPowerOfTau(k) | SrsFormat::PerpetualPowerOfTauUncompressed(k) => {
+                let compression = match format {
+                    SrsFormat::PerpetualPowerOfTau(_) => Compression::Compressed,
+                    _ => Compression::Uncompressed,
+                };
+                let desired_k = desired_k.unwrap_or(k);
+                let accumulator =
+                    perpetual_powers_of_tau::read_sections(reader, k, compression, desired_k)?;
+                (accumulator, desired_k)
+            }
+            SrsFormat::SnarkJs => {
+                let desired_k = match desired_k {
+                    Some(desired_k) => desired_k,
+                    None => snarkjs::read_k::<M, _>(reader)?,
+                };
+                (snarkjs::read_sections(reader, desired_k)?, desired_k)
+            }
+            SrsFormat::Pse => return Err(SrsError::UnsupportedFormat("PSE")),
+            SrsFormat::PseRaw | SrsFormat::PseRawUnchecked => {
+                return Err(SrsError::UnsupportedFormat("PSE raw"))
+            }
+            SrsFormat::AztecIgnition(_) => {
+                return Err(SrsError::UnsupportedFormat("Aztec Ignition"))
+            }
+        };
+
+        let accumulator = Self::from_sections(desired_k, accumulator);
+        if !accumulator.validate() {
+            return Err(SrsError::ValidationFailed("same ratio"));
+        }
+        Ok(accumulator)
+    }
+
+    fn from_sections(k: u32, accumulator: Accumulator<M>) -> Self {
+        fn non_identity<C: PrimeCurveAffine>(points: Vec<C>) -> Option<Vec<C>> {
+            (!points.iter().all(|point| bool::from(point.is_identity()))).then_some(points)
+        }
+
+        let mut tau_g2 = accumulator.tau_g2;
+        while tau_g2.len() > 2 && bool::from(tau_g2[tau_g2.len() - 1].is_identity()) {
+            tau_g2.pop();
+        }
+
+        Self {
+            k,
+            tau_g1: accumulator.tau_g1,
+            tau_g2,
+            alpha_tau_g1: non_identity(accumulator.alpha_tau_g1),
+            beta_tau_g1: non_identity(accumulator.beta_tau_g1),
+            beta_g2: (!bool::from(accumulator.beta_g2.is_identity()))
+                .then_some(accumulator.beta_g2),
+        }
+    }
+
+    /// Checks all sections in G1 and G2 are successive powers of tau, and
+    /// beta_g2 has the same beta as beta_tau_g1. Powers in G2 can't be
+    /// checked when `k` is 0, as there is only one power in G1.
+    fn validate(&self) -> bool {
+        let g1 = self.tau_g1[0];
+        let (g2, s_g2) = (self.tau_g2[0], self.tau_g2[1]);
+        [
+            Some(&self.tau_g1),
+            self.alpha_tau_g1.as_ref(),
+            self.beta_tau_g1.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .all(|g1s| same_ratio::<M>(g1s, g2, s_g2))
+            && self
+                .tau_g1
+                .get(1)
+                .map_or(true, |s_g1| same_ratio_g2::<M>(&self.tau_g2, g1, *s_g1))
+            && match (&self.beta_tau_g1, self.beta_g2) {
+                (Some(beta_tau_g1), Some(beta_g2)) => {
+                    same_ratio_pair::<M>((g1, beta_tau_g1[0]), (g2, beta_g2))
+                }
+                (None, None) => true,
+                _ => false,
+            }
+    }
+}
+
+impl<M: MultiMillerLoop> From<PhaseOneAccumulator<M>> for Srs<M> {
+    fn from(mut accumulator: PhaseOneAccumulator<M>) -> Self {
+        let k = accumulator.k;
+        accumulator.tau_g1.truncate(1 << k);
+        let g_lagrange = g_to_lagrange(&accumulator.tau_g1, k);
+        Srs {
+            k,
+            g: accumulator.tau_g1,
+            g_lagrange,
+            g2: accumulator.tau_g2[0],
+            s_g2: accumulator.tau_g2[1],
+        }
+    }
+}
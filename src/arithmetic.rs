@@ -3,34 +3,97 @@
 //! This module provides common utilities, traits and structures for group,
 //! field and polynomial arithmetic.
 
+use crate::{
+    util::{ec_point_raw_size, pse::decode_raw_ec_points},
+    Phase, Progress, SrsError,
+};
 use halo2_curves::{
     group::{
-        ff::{Field, PrimeField},
+        ff::{BatchInvert, Field, PrimeField},
         Curve, Group as _,
     },
     pairing::{MillerLoopResult, MultiMillerLoop},
+    serde::SerdeObject,
     CurveAffine, FieldExt, Group,
 };
 use rand_core::OsRng;
-use rayon as multicore;
-use std::{io, iter};
+use rayon::{self as multicore, prelude::*};
+use std::{io, iter, mem, ops::Range};
 
 pub fn same_ratio<M: MultiMillerLoop>(
     g1s: &[M::G1Affine],
     g2: M::G2Affine,
     s_g2: M::G2Affine,
 ) -> bool {
-    let coeffs = iter::repeat_with(|| M::Scalar::random(OsRng))
-        .take(g1s.len() - 1)
-        .collect::<Vec<_>>();
+    same_ratio_in_chunks::<M>(g1s, g2, s_g2, g1s.len(), |_| Ok(())).unwrap()
+}
 
-    let lhs = best_multiexp(&coeffs, &g1s[..g1s.len() - 1]);
-    let rhs = best_multiexp(&coeffs, &g1s[1..g1s.len()]);
+/// Same as [`same_ratio`], but computes the MSMs in chunks of `chunk_size`
+/// ratios, and calls `advance` with the number of ratios after each chunk,
+/// which stops early on error.
+pub(crate) fn same_ratio_in_chunks<M: MultiMillerLoop>(
+    g1s: &[M::G1Affine],
+    g2: M::G2Affine,
+    s_g2: M::G2Affine,
+    chunk_size: usize,
+    mut advance: impl FnMut(usize) -> Result<(), SrsError>,
+) -> Result<bool, SrsError> {
+    let n = g1s.len() - 1;
+    let mut lhs = M::G1::identity();
+    let mut rhs = M::G1::identity();
+    for start in (0..n).step_by(chunk_size) {
+        let end = (start + chunk_size).min(n);
+        let coeffs = iter::repeat_with(|| M::Scalar::random(OsRng))
+            .take(end - start)
+            .collect::<Vec<_>>();
+        lhs += best_multiexp(&coeffs, &g1s[start..end]);
+        rhs += best_multiexp(&coeffs, &g1s[start + 1..end + 1]);
+        advance(end - start)?;
+    }
 
-    M::multi_miller_loop(&[(&lhs.into(), &s_g2.into()), (&rhs.into(), &(-g2).into())])
-        .final_exponentiation()
-        .is_identity()
-        .into()
+    Ok(
+        M::multi_miller_loop(&[(&lhs.into(), &s_g2.into()), (&rhs.into(), &(-g2).into())])
+            .final_exponentiation()
+            .is_identity()
+            .into(),
+    )
+}
+
+/// Checks `g_lagrange` is the inverse FFT of `g` by committing a random
+/// polynomial $p(X) = \sum_j z^j X^j$ in both bases, where the evaluations
+/// $p(\omega^i) = (z^n - 1) / (z \omega^i - 1)$ are used for `g_lagrange`.
+pub fn same_lagrange<C: CurveAffine>(g: &[C], g_lagrange: &[C]) -> bool {
+    assert_eq!(g.len(), g_lagrange.len());
+    assert!(g.len().is_power_of_two());
+
+    let n = g.len();
+    let omega = root_of_unity::<C::Scalar>(n.trailing_zeros());
+    let z = C::Scalar::random(OsRng);
+
+    let mut coeffs = vec![C::Scalar::zero(); n];
+    parallelize(&mut coeffs, |coeffs, start| {
+        let mut z_pow = z.pow_vartime(&[start as u64]);
+        for coeff in coeffs.iter_mut() {
+            *coeff = z_pow;
+            z_pow *= z;
+        }
+    });
+
+    let z_n_minus_one = z.pow_vartime(&[n as u64]) - C::Scalar::one();
+    let mut evals = vec![C::Scalar::zero(); n];
+    parallelize(&mut evals, |evals, start| {
+        let mut omega_pow = omega.pow_vartime(&[start as u64]);
+        for eval in evals.iter_mut() {
+            *eval = z * omega_pow - C::Scalar::one();
+            omega_pow *= omega;
+        }
+        evals.iter_mut().batch_invert();
+        for eval in evals.iter_mut() {
+            *eval *= z_n_minus_one;
+        }
+    });
+
+    best_multiexp(&coeffs, g) == best_multiexp(&evals, g_lagrange)
 }
 
 fn multiexp_serial<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C], acc: &mut C::Curve) {
@@ -168,15 +231,6 @@ pub(crate) fn best_multiexp<C: CurveAffine>(coeffs: &[C::Scalar], bases: &[C]) -
 ///
 /// This will use multithreading if beneficial.
 pub(crate) fn best_fft<G: Group>(a: &mut [G], omega: G::Scalar, log_n: u32) {
-    fn bitreverse(mut n: usize, l: usize) -> usize {
-        let mut r = 0;
-        for _ in 0..l {
-            r = (r << 1) | (n & 1);
-            n >>= 1;
-        }
-        r
-    }
-
     let threads = multicore::current_num_threads();
     let log_threads = log2_floor(threads);
     let n = a.len() as usize;
@@ -232,6 +286,15 @@ pub(crate) fn best_fft<G: Group>(a: &mut [G], omega: G::Scalar, log_n: u32) {
     }
 }
 
+fn bitreverse(mut n: usize, l: usize) -> usize {
+    let mut r = 0;
+    for _ in 0..l {
+        r = (r << 1) | (n & 1);
+        n >>= 1;
+    }
+    r
+}
+
 /// This perform recursive butterfly arithmetic
 pub(crate) fn recursive_butterfly_arithmetic<G: Group>(
     a: &mut [G],
@@ -272,6 +335,68 @@ pub(crate) fn recursive_butterfly_arithmetic<G: Group>(
     }
 }
 
+/// Performs the same FFT as [`best_fft`], but in `log_n` butterfly rounds in
+/// turn, each over all of `a` in parallel, and calls `advance` with the
+/// number of points after each round, which stops early on error.
+fn fft_in_rounds<G: Group>(
+    a: &mut [G],
+    omega: G::Scalar,
+    log_n: u32,
+    mut advance: impl FnMut(usize) -> Result<(), SrsError>,
+) -> Result<(), SrsError> {
+    let n = a.len();
+    assert_eq!(n, 1 << log_n);
+
+    for k in 0..n {
+        let rk = bitreverse(k, log_n as usize);
+        if k < rk {
+            a.swap(rk, k);
+        }
+    }
+
+    let twiddles: Vec<_> = (0..n / 2)
+        .scan(G::Scalar::one(), |w, _| {
+            let tw = *w;
+            w.group_scale(&omega);
+            Some(tw)
+        })
+        .collect();
+
+    let mut chunk = 2;
+    let mut twiddle_chunk = n / 2;
+    for _ in 0..log_n {
+        a.par_chunks_mut(chunk).for_each(|coeffs| {
+            let (left, right) = coeffs.split_at_mut(chunk / 2);
+            left.par_iter_mut()
+                .zip(right.par_iter_mut())
+                .enumerate()
+                .for_each(|(i, (a, b))| {
+                    let mut t = *b;
+                    if i != 0 {
+                        t.group_scale(&twiddles[i * twiddle_chunk]);
+                    }
+                    *b = *a;
+                    a.group_add(&t);
+                    b.group_sub(&t);
+                });
+        });
+        chunk *= 2;
+        twiddle_chunk /= 2;
+        advance(n)?;
+    }
+
+    Ok(())
+}
+
+/// Returns a primitive `2^k`-th root of unity.
+pub(crate) fn root_of_unity<F: FieldExt>(k: u32) -> F {
+    let mut omega = F::root_of_unity();
+    for _ in k..F::S {
+        omega = omega.square();
+    }
+    omega
+}
+
 /// Convert coefficient bases group elements to lagrange basis by inverse FFT.
 pub(crate) fn g_to_lagrange<C: CurveAffine>(g: &[C], k: u32) -> Vec<C> {
     let n_inv = C::Scalar::TWO_INV.pow_vartime(&[k as u64, 0, 0, 0]);
@@ -280,30 +405,202 @@ pub(crate) fn g_to_lagrange<C: CurveAffine>(g: &[C], k: u32) -> Vec<C> {
         omega_inv = omega_inv.square();
     }
 
-    let mut g_projective = vec![C::Curve::default(); g.len()];
-    parallelize(&mut g_projective, |g_projective, start| {
-        for (i, g_projective) in g_projective.iter_mut().enumerate() {
-            *g_projective = g[start + i].to_curve();
+    fft_points(g, omega_inv, k, n_inv)
+}
+
+/// Same as [`g_to_lagrange`], but does the FFT by [`fft_in_rounds`] with
+/// `advance`.
+pub(crate) fn g_to_lagrange_in_rounds<C: CurveAffine>(
+    g: &[C],
+    k: u32,
+    advance: impl FnMut(usize) -> Result<(), SrsError>,
+) -> Result<Vec<C>, SrsError> {
+    let n_inv = C::Scalar::TWO_INV.pow_vartime(&[k as u64, 0, 0, 0]);
+    let omega_inv = root_of_unity::<C::Scalar>(k).invert().unwrap();
+
+    let mut projective = to_projective(g);
+    fft_in_rounds(&mut projective, omega_inv, k, advance)?;
+    Ok(to_affine(projective, n_inv))
+}
+
+/// Convert coefficient bases group elements to lagrange basis by inverse FFT
+/// like [`g_to_lagrange`], but out of core for `k` too large to fit in memory.
+///
+/// It uses the four-step FFT, which views the `n = n_1 * n_2` points as a
+/// matrix with `n_1` columns, does FFT on each column, multiplies by twiddle
+/// factors, then does FFT on each row. Blocks of columns or rows are loaded
+/// in turn with about `memory_budget` bytes, counting each point by
+/// [`out_of_core_point_size`] for its projective, affine and raw buffers, and
+/// the intermediate results are kept in `scratch` with space of `2n` points in
+/// raw format. Points are read by range with `read_g`, and the results are passed
+/// to `write` in order.
+///
+/// Each pass over the `n` points is reported to `progress` in blocks, so the
+/// total is `3n`.
+pub(crate) fn g_to_lagrange_out_of_core<C, S>(
+    mut read_g: impl FnMut(Range<usize>) -> Result<Vec<C>, SrsError>,
+    k: u32,
+    scratch: &mut S,
+    memory_budget: usize,
+    mut write: impl FnMut(&[C]) -> Result<(), SrsError>,
+    progress: &Progress,
+) -> Result<(), SrsError>
+where
+    C: CurveAffine + SerdeObject,
+    S: io::Read + io::Write + io::Seek,
+{
+    let n = 1 << k;
+    let total = 3 * n as u64;
+    let mut processed = 0;
+    let mut advance = |points: usize| {
+        processed += points as u64;
+        progress.advance(Phase::Lagrange, processed, total)
+    };
+    progress.start(Phase::Lagrange, total)?;
+
+    let budget = (memory_budget / out_of_core_point_size::<C>()).max(1);
+    if n <= budget {
+        write(&g_to_lagrange(&read_g(0..n)?, k))?;
+        advance(3 * n)?;
+        progress.finish(Phase::Lagrange);
+        return Ok(());
+    }
+
+    let raw_size = ec_point_raw_size::<C>();
+    let (k_1, k_2) = (k - k / 2, k / 2);
+    let (n_1, n_2) = (1 << k_1, 1 << k_2);
+    let n_inv = C::Scalar::TWO_INV.pow_vartime(&[k as u64, 0, 0, 0]);
+    let omega_inv = root_of_unity::<C::Scalar>(k).invert().unwrap();
+    let omega_inv_1 = omega_inv.pow_vartime(&[n_2 as u64]);
+    let omega_inv_2 = omega_inv.pow_vartime(&[n_1 as u64]);
+
+    // FFT on each column `g[j_1 + n_1 * j_2]` over `j_2`, multiplied by
+    // `omega_inv^(j_1 * i_2) / n`, and stored transposed at `j_1 + n_1 * i_2`
+    // in the first half of scratch.
+    let width = 1 << log2_floor((budget / n_2).max(1)).min(k_1);
+    let mut columns = vec![C::Curve::identity(); width * n_2];
+    let mut affine = vec![C::identity(); width * n_2];
+    let mut bytes = Vec::with_capacity(width * raw_size);
+    for start in (0..n_1).step_by(width) {
+        for j_2 in 0..n_2 {
+            let offset = start + n_1 * j_2;
+            for (j_1, point) in read_g(offset..offset + width)?.iter().enumerate() {
+                columns[j_1 * n_2 + j_2] = point.to_curve();
+            }
         }
-    });
+        columns
+            .par_chunks_mut(n_2)
+            .enumerate()
+            .for_each(|(j_1, column)| {
+                best_fft(column, omega_inv_2, k_2);
+                let omega_inv_j_1 = omega_inv.pow_vartime(&[(start + j_1) as u64]);
+                let mut twiddle = n_inv;
+                for point in column.iter_mut() {
+                    *point *= twiddle;
+                    twiddle *= omega_inv_j_1;
+                }
+            });
+        batch_normalize(&columns, &mut affine);
 
-    let mut g_lagrange_projective = g_projective;
-    best_fft(&mut g_lagrange_projective, omega_inv, k);
-    parallelize(&mut g_lagrange_projective, |g, _| {
-        for g in g.iter_mut() {
-            *g *= n_inv;
+        for i_2 in 0..n_2 {
+            bytes.clear();
+            for j_1 in 0..width {
+                affine[j_1 * n_2 + i_2].write_raw(&mut bytes)?;
+            }
+            let offset = start + n_1 * i_2;
+            scratch.seek(io::SeekFrom::Start((offset * raw_size) as u64))?;
+            scratch.write_all(&bytes)?;
         }
+        advance(width * n_2)?;
+    }
+
+    // FFT on each row over `j_1`, whose results are `g_lagrange[i_2 + n_2 *
+    // i_1]` stored in order in the second half of scratch.
+    let height = 1 << log2_floor((budget / n_1).max(1)).min(k_2);
+    let mut rows = vec![C::Curve::identity(); height * n_1];
+    let mut affine = vec![C::identity(); height * n_1];
+    let mut bytes = vec![0; height * n_1 * raw_size];
+    for start in (0..n_2).step_by(height) {
+        scratch.seek(io::SeekFrom::Start((start * n_1 * raw_size) as u64))?;
+        scratch.read_exact(&mut bytes)?;
+        let points = decode_raw_ec_points::<C, false>(&bytes, "scratch")?;
+        parallelize(&mut rows, |rows, offset| {
+            for (row, point) in rows.iter_mut().zip(points[offset..].iter()) {
+                *row = point.to_curve();
+            }
+        });
+        rows.par_chunks_mut(n_1)
+            .for_each(|row| best_fft(row, omega_inv_1, k_1));
+        batch_normalize(&rows, &mut affine);
+
+        let mut transposed = Vec::with_capacity(height * raw_size);
+        for i_1 in 0..n_1 {
+            transposed.clear();
+            for i_2 in 0..height {
+                affine[i_2 * n_1 + i_1].write_raw(&mut transposed)?;
+            }
+            let offset = n + start + n_2 * i_1;
+            scratch.seek(io::SeekFrom::Start((offset * raw_size) as u64))?;
+            scratch.write_all(&transposed)?;
+        }
+        advance(height * n_1)?;
+    }
+
+    let chunk_size = 1 << log2_floor(budget);
+    let mut bytes = vec![0; chunk_size * raw_size];
+    scratch.seek(io::SeekFrom::Start((n * raw_size) as u64))?;
+    for _ in (0..n).step_by(chunk_size) {
+        scratch.read_exact(&mut bytes)?;
+        write(&decode_raw_ec_points::<C, false>(&bytes, "scratch")?)?;
+        advance(chunk_size)?;
+    }
+    progress.finish(Phase::Lagrange);
+
+    Ok(())
+}
+
+/// Returns bytes in memory per point loaded by [`g_to_lagrange_out_of_core`],
+/// which holds it in projective, affine and raw format at the same time.
+pub(crate) fn out_of_core_point_size<C: CurveAffine + SerdeObject>() -> usize {
+    mem::size_of::<C::Curve>() + 2 * mem::size_of::<C>() + ec_point_raw_size::<C>()
+}
+
+fn batch_normalize<C: CurveAffine>(projective: &[C::Curve], affine: &mut [C]) {
+    parallelize(affine, |affine, start| {
+        C::Curve::batch_normalize(&projective[start..(start + affine.len())], affine);
     });
+}
 
-    let mut g_lagrange = vec![C::identity(); 1 << k];
-    parallelize(&mut g_lagrange, |g_lagrange, starts| {
-        C::Curve::batch_normalize(
-            &g_lagrange_projective[starts..(starts + g_lagrange.len())],
-            g_lagrange,
-        );
+fn fft_points<C: CurveAffine>(points: &[C], omega: C::Scalar, k: u32, scale: C::Scalar) -> Vec<C> {
+    let mut projective = to_projective(points);
+    best_fft(&mut projective, omega, k);
+    to_affine(projective, scale)
+}
+
+fn to_projective<C: CurveAffine>(points: &[C]) -> Vec<C::Curve> {
+    let mut projective = vec![C::Curve::default(); points.len()];
+    parallelize(&mut projective, |projective, start| {
+        for (i, projective) in projective.iter_mut().enumerate() {
+            *projective = points[start + i].to_curve();
+        }
     });
+    projective
+}
+
+/// Normalizes `projective` to affine after multiplying each by `scale`.
+fn to_affine<C: CurveAffine>(mut projective: Vec<C::Curve>, scale: C::Scalar) -> Vec<C> {
+    if scale != C::Scalar::one() {
+        parallelize(&mut projective, |projective, _| {
+            for projective in projective.iter_mut() {
+                *projective *= scale;
+            }
+        });
+    }
+
+    let mut affine = vec![C::identity(); projective.len()];
+    batch_normalize(&projective, &mut affine);
 
-    g_lagrange
+    affine
 }
 
 /// This simple utility function will parallelize an operation that is to be
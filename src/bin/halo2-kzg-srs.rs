@@ -0,0 +1,437 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use halo2_curves::{bn256::Bn256, pairing::MultiMillerLoop, serde::SerdeObject};
+use halo2_kzg_srs::{
+    util::{aztec_ignition, snarkjs},
+    Detection, Phase, Progress, ProgressSink, Srs, SrsError, SrsFormat, SrsReader, SrsView,
+    StreamingSrsWriter,
+};
+use std::{
+    error::Error,
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+};
+
+#[derive(Parser)]
+#[clap(
+    version,
+    about = "Convert, verify and inspect KZG SRS in various formats"
+)]
+struct Cli {
+    /// Curve of SRS, taken from the header for snarkjs, otherwise default to
+    /// bn256
+    #[clap(long, value_enum, global = true)]
+    curve: Option<Curve>,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Curve {
+    Bn256,
+}
+
+impl Curve {
+    fn name(self) -> &'static str {
+        self.to_possible_value().unwrap().get_name()
+    }
+
+    fn matches(self, header: &snarkjs::Header) -> bool {
+        match self {
+            Curve::Bn256 => header.is_curve::<Bn256>(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Pse,
+    PseRaw,
+    PseRawUnchecked,
+    PerpetualPowersOfTau,
+    PerpetualPowersOfTauUncompressed,
+    Snarkjs,
+    AztecIgnition,
+}
+
+#[derive(Args)]
+struct Source {
+    /// Source file, or the directory containing `transcript00.dat` to
+    /// `transcriptNN.dat` for Aztec Ignition
+    src: PathBuf,
+    /// Format of source, detected from the file if not specified
+    #[clap(long = "format", visible_alias = "from", value_enum)]
+    format: Option<Format>,
+    /// `k` of the whole ceremony, which is needed to locate the G2 points of
+    /// Perpetual Powers of Tau, inferred from the file size if not specified
+    #[clap(long)]
+    source_k: Option<u32>,
+}
+
+impl Source {
+    fn detect<M: MultiMillerLoop>(&self) -> Result<Detection, SrsError>
+    where
+        M::G1Affine: SerdeObject,
+        M::G2Affine: SerdeObject,
+    {
+        SrsFormat::detect::<M, _>(&mut self.open()?)
+    }
+
+    fn srs_format<M: MultiMillerLoop>(&self) -> Result<SrsFormat, SrsError>
+    where
+        M::G1Affine: SerdeObject,
+        M::G2Affine: SerdeObject,
+    {
+        match (self.format, self.source_k) {
+            (None, _)
+            | (
+                Some(Format::PerpetualPowersOfTau | Format::PerpetualPowersOfTauUncompressed),
+                None,
+            ) => Ok(self.detect::<M>()?.format),
+            (Some(format), source_k) => Ok(to_srs_format(
+                format,
+                &self.src,
+                source_k.unwrap_or_default(),
+            )),
+        }
+    }
+
+    /// Returns the curve of source if it's in snarkjs format, by its header.
+    fn snarkjs_curve(&self) -> Result<Option<Curve>, SrsError> {
+        if !matches!(self.format, None | Some(Format::Snarkjs)) {
+            return Ok(None);
+        }
+        let mut reader = self.open()?;
+        let mut magic = [0; 4];
+        if reader.read_exact(&mut magic).is_err() || magic != snarkjs::MAGIC {
+            return Ok(None);
+        }
+        let header = snarkjs::read_header_unchecked(&mut reader)?;
+        Curve::value_variants()
+            .iter()
+            .find(|curve| curve.matches(&header))
+            .copied()
+            .map(Some)
+            .ok_or_else(|| {
+                SrsError::UnexpectedHeader(format!(
+                    "Unsupported curve with field modulus 0x{:x}",
+                    header.modulus
+                ))
+            })
+    }
+
+    fn open(&self) -> io::Result<File> {
+        File::open(match self.format {
+            Some(Format::AztecIgnition) => aztec_ignition::transcript_path(&self.src, 0),
+            _ => self.src.clone(),
+        })
+    }
+
+    fn read<M: MultiMillerLoop>(
+        &self,
+        desired_k: Option<u32>,
+        progress: &Progress,
+    ) -> Result<Srs<M>, SrsError>
+    where
+        M::G1Affine: SerdeObject,
+        M::G2Affine: SerdeObject,
+    {
+        let format = self.srs_format::<M>()?;
+        if matches!(format, SrsFormat::PseRaw | SrsFormat::PseRawUnchecked) {
+            // Map the file to only decode the points needed
+            let view = SrsView::open(&self.src)?;
+            let desired_k = desired_k.unwrap_or_else(|| view.k());
+            return match format {
+                SrsFormat::PseRaw => view.to_srs::<true>(desired_k),
+                _ => view.to_srs::<false>(desired_k),
+            };
+        }
+
+        Srs::try_read_with_progress(&mut self.open()?, format, desired_k, progress)
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Convert SRS into other formats, with an output file for each `k`
+    Convert {
+        #[clap(flatten)]
+        source: Source,
+        /// Destination file path prefix, which will be appended with format
+        /// suffix and `k`
+        dst_prefix: String,
+        /// Output formats, can be specified multiple times
+        #[clap(long, value_enum, required = true)]
+        to: Vec<Format>,
+        /// Largest `k` to output, default to the largest one in source
+        #[clap(long)]
+        k: Option<u32>,
+        /// Smallest `k` to output
+        #[clap(long, default_value_t = 1)]
+        min_k: u32,
+        /// Convert without loading the whole SRS, keeping about this many MiB
+        /// of points in memory, with a `.scratch` file next to each output.
+        /// It only supports PSE output formats, and doesn't validate the
+        /// source, which can be done separately by `verify`.
+        #[clap(long)]
+        memory_budget: Option<usize>,
+    },
+    /// Verify the structure of SRS
+    Verify {
+        #[clap(flatten)]
+        source: Source,
+    },
+    /// Print k, format, generators and file hash of SRS
+    Inspect {
+        #[clap(flatten)]
+        source: Source,
+    },
+    /// Downsize SRS to a smaller `k` in the same format
+    Downsize {
+        #[clap(flatten)]
+        source: Source,
+        dst: PathBuf,
+        #[clap(long)]
+        to_k: u32,
+    },
+}
+
+/// Suffix appended to the destination prefix before `k`, which follows the
+/// naming of the converted SRS we host.
+fn suffix(format: &SrsFormat) -> &'static str {
+    match format {
+        SrsFormat::Pse => "",
+        // Same layout as `PseRaw`, which only differs in how it's read
+        SrsFormat::PseRaw | SrsFormat::PseRawUnchecked => "raw-",
+        SrsFormat::PerpetualPowerOfTau(_) => "perpetual-powers-of-tau-",
+        SrsFormat::PerpetualPowerOfTauUncompressed(_) => "perpetual-powers-of-tau-uncompressed-",
+        SrsFormat::SnarkJs => "snarkjs-",
+        SrsFormat::AztecIgnition(_) => "aztec-ignition-",
+    }
+}
+
+fn to_srs_format(format: Format, path: &Path, k: u32) -> SrsFormat {
+    match format {
+        Format::Pse => SrsFormat::Pse,
+        Format::PseRaw => SrsFormat::PseRaw,
+        Format::PseRawUnchecked => SrsFormat::PseRawUnchecked,
+        Format::PerpetualPowersOfTau => SrsFormat::PerpetualPowerOfTau(k),
+        Format::PerpetualPowersOfTauUncompressed => SrsFormat::PerpetualPowerOfTauUncompressed(k),
+        Format::Snarkjs => SrsFormat::SnarkJs,
+        Format::AztecIgnition => SrsFormat::AztecIgnition(path.to_path_buf()),
+    }
+}
+
+fn write<M: MultiMillerLoop>(
+    srs: &Srs<M>,
+    path: impl AsRef<Path>,
+    format: SrsFormat,
+) -> Result<(), SrsError>
+where
+    M::G1Affine: SerdeObject,
+    M::G2Affine: SerdeObject,
+{
+    let mut writer = BufWriter::new(File::create(path)?);
+    srs.try_write_as(&mut writer, format)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Renders progress on stderr as a bar redrawn in place.
+struct ProgressBar;
+
+impl ProgressSink for ProgressBar {
+    fn started(&self, phase: Phase, total: u64) {
+        self.advanced(phase, 0, total);
+    }
+
+    fn advanced(&self, phase: Phase, processed: u64, total: u64) {
+        const WIDTH: usize = 40;
+        let ratio = (processed as f64 / total.max(1) as f64).clamp(0.0, 1.0);
+        let filled = (ratio * WIDTH as f64) as usize;
+        eprint!(
+            "\r{phase:<24} [{}{}] {:>3.0}%",
+            "#".repeat(filled),
+            " ".repeat(WIDTH - filled),
+            100.0 * ratio
+        );
+        io::stderr().flush().ok();
+    }
+
+    fn finished(&self, _: Phase) {
+        eprintln!();
+    }
+}
+
+fn create_read_write(path: impl AsRef<Path>) -> io::Result<File> {
+    OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(path)
+}
+
+fn file_hash(mut reader: impl io::Read) -> io::Result<String> {
+    let mut state = blake2b_simd::State::new();
+    io::copy(&mut reader, &mut state)?;
+    Ok(state.finalize().to_hex().to_string())
+}
+
+fn run<M: MultiMillerLoop>(command: Command) -> Result<(), Box<dyn Error>>
+where
+    M::G1Affine: SerdeObject,
+    M::G2Affine: SerdeObject,
+{
+    let progress = Progress::new().with_sink(ProgressBar);
+    match command {
+        Command::Convert {
+            source,
+            dst_prefix,
+            to,
+            k,
+            min_k,
+            memory_budget: Some(memory_budget),
+        } => {
+            // Checked upfront so no output is created for an unsupported format.
+            if !to
+                .iter()
+                .all(|to| matches!(to, Format::Pse | Format::PseRaw | Format::PseRawUnchecked))
+            {
+                return Err(SrsError::UnsupportedFormat("streaming writer").into());
+            }
+            let format = source.srs_format::<M>()?;
+            let mut reader = source.open()?;
+            let source_k = SrsReader::<M>::read_k(&format, &mut reader)?;
+            let k = k.unwrap_or(source_k);
+            if k > source_k {
+                return Err(SrsError::RequestedKTooLarge {
+                    requested: k,
+                    available: source_k,
+                }
+                .into());
+            }
+            let g2s = SrsReader::<M>::read_g2s(&format, &mut reader)?;
+            for k in min_k..=k {
+                for to in to.iter() {
+                    let to = to_srs_format(*to, dst_prefix.as_ref(), k);
+                    let path = format!("{dst_prefix}{}{k}", suffix(&to));
+                    let scratch_path = format!("{path}.scratch");
+                    let written = (|| -> Result<(), SrsError> {
+                        let mut writer = StreamingSrsWriter::<M, _, _>::new(
+                            create_read_write(&path)?,
+                            create_read_write(&scratch_path)?,
+                            to,
+                            memory_budget << 20,
+                        )?
+                        .with_progress(progress.clone());
+                        writer.copy_g1s_from(&mut reader, &format, 0..1 << k)?;
+                        writer.finish(g2s)?;
+                        Ok(())
+                    })();
+                    let removed = fs::remove_file(&scratch_path);
+                    written?;
+                    removed?;
+                    println!("Written {path}");
+                }
+            }
+        }
+        Command::Convert {
+            source,
+            dst_prefix,
+            to,
+            k,
+            min_k,
+            memory_budget: None,
+        } => {
+            let srs = source.read::<M>(k, &progress)?;
+            let formats = to
+                .iter()
+                .map(|format| to_srs_format(*format, dst_prefix.as_ref(), srs.k))
+                .collect::<Vec<_>>();
+            srs.write_all_downsized_with_progress(
+                min_k..=srs.k,
+                &formats,
+                |k, format| {
+                    let path = format!("{dst_prefix}{}{k}", suffix(format));
+                    Ok(BufWriter::new(File::create(path)?))
+                },
+                |k, format, _| {
+                    println!("Written {dst_prefix}{}{k}", suffix(format));
+                    Ok(())
+                },
+                &progress,
+            )?;
+        }
+        Command::Verify { source } => {
+            let report = source.read::<M>(None, &progress)?.verify()?;
+            println!("SRS is well formed");
+            println!("G1 generator: {:?}", report.g1_generator);
+            println!("G2 generator: {:?}", report.g2_generator);
+            if !report.is_canonical_g1_generator || !report.is_canonical_g2_generator {
+                println!("Warning: generators are not the canonical ones of the curve");
+            }
+        }
+        Command::Inspect { source } => {
+            let srs = source.read::<M>(None, &progress)?;
+            println!("k: {}", srs.k);
+            match source.format {
+                Some(format) => {
+                    println!("format: {}", format.to_possible_value().unwrap().get_name())
+                }
+                None => {
+                    let detection = source.detect::<M>()?;
+                    println!(
+                        "format: {:?} (detected with {:?} confidence)",
+                        detection.format, detection.confidence
+                    );
+                }
+            }
+            println!("g[0]: {:?}", srs.g[0]);
+            println!("g2: {:?}", srs.g2);
+            println!("s_g2: {:?}", srs.s_g2);
+            println!("blake2b: {}", file_hash(source.open()?)?);
+        }
+        Command::Downsize { source, dst, to_k } => {
+            let format = match source.srs_format::<M>()? {
+                SrsFormat::PerpetualPowerOfTau(_) => SrsFormat::PerpetualPowerOfTau(to_k),
+                SrsFormat::PerpetualPowerOfTauUncompressed(_) => {
+                    SrsFormat::PerpetualPowerOfTauUncompressed(to_k)
+                }
+                format => format,
+            };
+            let srs = source.read::<M>(Some(to_k), &progress)?;
+            write(&srs, &dst, format)?;
+        }
+    }
+    Ok(())
+}
+
+impl Command {
+    fn source(&self) -> &Source {
+        match self {
+            Command::Convert { source, .. }
+            | Command::Verify { source }
+            | Command::Inspect { source }
+            | Command::Downsize { source, .. } => source,
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    let curve = match (cli.curve, cli.command.source().snarkjs_curve()?) {
+        (Some(curve), Some(file_curve)) if curve != file_curve => {
+            return Err(format!(
+                "Source is on curve {} but --curve {} is specified",
+                file_curve.name(),
+                curve.name()
+            )
+            .into())
+        }
+        (curve, file_curve) => curve.or(file_curve).unwrap_or(Curve::Bn256),
+    };
+    match curve {
+        Curve::Bn256 => run::<Bn256>(cli.command),
+    }
+}
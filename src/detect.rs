@@ -0,0 +1,100 @@
+use crate::{
+    util::{perpetual_powers_of_tau, pse, snarkjs, Compression},
+    SrsError, SrsFormat,
+};
+use halo2_curves::{pairing::MultiMillerLoop, serde::SerdeObject};
+use std::io;
+
+/// Largest `k` to try when inferring from the file size.
+const MAX_K: u32 = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    /// File size matches multiple layouts, and the first one is picked.
+    Low,
+    /// File size matches exactly one layout.
+    Medium,
+    /// Magic and header of the file are recognized.
+    High,
+}
+
+/// Result of [`SrsFormat::detect`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Detection {
+    pub format: SrsFormat,
+    /// Largest `k` available in the file.
+    pub k: u32,
+    pub confidence: Confidence,
+}
+
+impl SrsFormat {
+    /// Detects the format of the file by its magic, header or size.
+    ///
+    /// [`SrsFormat::AztecIgnition`] is not detected since it is spread over
+    /// multiple files.
+    pub fn detect<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        reader: &mut R,
+    ) -> Result<Detection, SrsError>
+    where
+        M::G1Affine: SerdeObject,
+        M::G2Affine: SerdeObject,
+    {
+        let size = reader.seek(io::SeekFrom::End(0))?;
+
+        let mut magic = [0; 4];
+        if size >= magic.len() as u64 {
+            reader.rewind()?;
+            reader.read_exact(&mut magic)?;
+        }
+        if magic == snarkjs::MAGIC {
+            let header = snarkjs::read_header::<M, _>(reader)?;
+            return Ok(Detection {
+                format: SrsFormat::SnarkJs,
+                k: header.power,
+                confidence: Confidence::High,
+            });
+        }
+
+        let mut candidates = Vec::new();
+
+        let pse_k = u32::from_le_bytes(magic);
+        if pse_k <= MAX_K {
+            if size == pse::file_size::<M, false>(pse_k) {
+                candidates.push((SrsFormat::Pse, pse_k));
+            }
+            if size == pse::file_size::<M, true>(pse_k) {
+                candidates.push((SrsFormat::PseRaw, pse_k));
+            }
+        }
+
+        for k in 0..=MAX_K {
+            for with_public_key in [true, false] {
+                let file_size = |compression| {
+                    perpetual_powers_of_tau::file_size::<M>(k, compression, with_public_key)
+                };
+                if size == file_size(Compression::Compressed) {
+                    candidates.push((SrsFormat::PerpetualPowerOfTau(k), k));
+                }
+                if size == file_size(Compression::Uncompressed) {
+                    candidates.push((SrsFormat::PerpetualPowerOfTauUncompressed(k), k));
+                }
+            }
+        }
+
+        let confidence = match candidates.len() {
+            0 => {
+                return Err(SrsError::UnexpectedHeader(format!(
+                    "No known format matches file size {size}"
+                )))
+            }
+            1 => Confidence::Medium,
+            _ => Confidence::Low,
+        };
+        let (format, k) = candidates.swap_remove(0);
+        Ok(Detection {
+            format,
+            k,
+            confidence,
+        })
+    }
+}
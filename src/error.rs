@@ -0,0 +1,81 @@
+use std::{error, fmt, io};
+
+#[derive(Debug)]
+pub enum SrsError {
+    Io(io::Error),
+    /// Requested `k` is larger than the one available in the source.
+    RequestedKTooLarge {
+        requested: u32,
+        available: u32,
+    },
+    /// Point at `index` of `section` is not a valid encoding.
+    InvalidPointEncoding {
+        index: usize,
+        section: &'static str,
+    },
+    /// Point at `index` of `section` is well encoded but not on curve.
+    NotOnCurve {
+        index: usize,
+        section: &'static str,
+    },
+    UnexpectedHeader(String),
+    /// Operation is not supported by the named format.
+    UnsupportedFormat(&'static str),
+    /// Named check failed when validating the read SRS.
+    ValidationFailed(&'static str),
+    /// Named check failed when verifying contribution at `index` of a
+    /// ceremony.
+    InvalidContribution {
+        index: usize,
+        check: &'static str,
+    },
+    /// Operation is cancelled by a [`CancellationToken`].
+    ///
+    /// [`CancellationToken`]: crate::CancellationToken
+    Cancelled,
+}
+
+impl fmt::Display for SrsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SrsError::Io(err) => write!(f, "I/O error: {err}"),
+            SrsError::RequestedKTooLarge {
+                requested,
+                available,
+            } => write!(
+                f,
+                "Requested k = {requested} is larger than available k = {available}"
+            ),
+            SrsError::InvalidPointEncoding { index, section } => {
+                write!(f, "Invalid point encoding at index {index} of {section}")
+            }
+            SrsError::NotOnCurve { index, section } => {
+                write!(f, "Point not on curve at index {index} of {section}")
+            }
+            SrsError::UnexpectedHeader(msg) => write!(f, "Unexpected header: {msg}"),
+            SrsError::UnsupportedFormat(format) => {
+                write!(f, "Unsupported operation for format {format}")
+            }
+            SrsError::ValidationFailed(check) => write!(f, "Validation failed: {check}"),
+            SrsError::InvalidContribution { index, check } => {
+                write!(f, "Contribution {index} failed check: {check}")
+            }
+            SrsError::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+impl error::Error for SrsError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            SrsError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SrsError {
+    fn from(err: io::Error) -> Self {
+        SrsError::Io(err)
+    }
+}
@@ -0,0 +1,51 @@
+use crate::SrsError;
+use halo2_curves::pairing::MultiMillerLoop;
+use std::{io, ops::Range};
+
+/// Reader of SRS in a specific format, which can be implemented outside of
+/// this crate to read with [`Srs::try_read_with`].
+///
+/// [`Srs::try_read_with`]: crate::Srs::try_read_with
+pub trait SrsReader<M: MultiMillerLoop> {
+    /// Returns the largest `k` available in the source.
+    fn read_k<R: io::Read + io::Seek>(&self, reader: &mut R) -> Result<u32, SrsError>;
+
+    /// Reads `g` in `range`.
+    fn read_g1s<R: io::Read + io::Seek>(
+        &self,
+        reader: &mut R,
+        range: Range<usize>,
+    ) -> Result<Vec<M::G1Affine>, SrsError>;
+
+    /// Reads `g_lagrange` with `2^k` points, or returns `None` if the source
+    /// doesn't have them, then they will be computed from `g`.
+    fn read_g1s_lagrange<R: io::Read + io::Seek>(
+        &self,
+        _reader: &mut R,
+        _k: u32,
+    ) -> Result<Option<Vec<M::G1Affine>>, SrsError> {
+        Ok(None)
+    }
+
+    /// Reads `[g2, s_g2]`.
+    fn read_g2s<R: io::Read + io::Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<[M::G2Affine; 2], SrsError>;
+}
+
+/// Writer of SRS in a specific format, which can be implemented outside of
+/// this crate to write with [`Srs::try_write_with`].
+///
+/// [`Srs::try_write_with`]: crate::Srs::try_write_with
+pub trait SrsWriter<M: MultiMillerLoop> {
+    /// Writes SRS with `2^k` points in `g` and `g_lagrange`.
+    fn write<W: io::Write>(
+        &self,
+        writer: &mut W,
+        k: u32,
+        g: &[M::G1Affine],
+        g_lagrange: &[M::G1Affine],
+        g2s: [M::G2Affine; 2],
+    ) -> Result<(), SrsError>;
+}
@@ -1,21 +1,114 @@
-use arithmetic::{g_to_lagrange, same_ratio};
-use byteorder::{LittleEndian, ReadBytesExt};
-use halo2_curves::{group::GroupEncoding, pairing::MultiMillerLoop, serde::SerdeObject};
-use std::io;
-use util::{perpetual_powers_of_tau, pse, snarkjs};
+use arithmetic::{
+    g_to_lagrange, g_to_lagrange_in_rounds, parallelize, same_lagrange, same_ratio,
+    same_ratio_in_chunks,
+};
+use halo2_curves::{
+    group::{ff::Field, prime::PrimeCurveAffine, Curve, Group},
+    pairing::{MillerLoopResult, MultiMillerLoop},
+    serde::SerdeObject,
+    CurveAffine, FieldExt,
+};
+use rand_core::RngCore;
+use rayon::prelude::*;
+use std::{
+    io,
+    ops::{Range, RangeInclusive},
+    path::PathBuf,
+};
+use util::{
+    aztec_ignition, perpetual_powers_of_tau,
+    perpetual_powers_of_tau::{compute_g2_s, same_ratio_pair},
+    pse, snarkjs, Compression,
+};
+
+/// Evaluates `$body` with `$reader` bound to the [`SrsReader`] of
+/// `$format: &SrsFormat`.
+macro_rules! with_reader {
+    ($format:expr, $reader:ident => $body:expr) => {
+        match $format {
+            SrsFormat::Pse => {
+                let $reader = &pse::Pse::<false>;
+                $body
+            }
+            SrsFormat::PseRaw => {
+                let $reader = &pse::Pse::<true>;
+                $body
+            }
+            SrsFormat::PseRawUnchecked => {
+                let $reader = &pse::Pse::<true, false>;
+                $body
+            }
+            SrsFormat::PerpetualPowerOfTau(k) => {
+                let $reader = &perpetual_powers_of_tau::PerpetualPowersOfTau {
+                    k: *k,
+                    compression: Compression::Compressed,
+                };
+                $body
+            }
+            SrsFormat::PerpetualPowerOfTauUncompressed(k) => {
+                let $reader = &perpetual_powers_of_tau::PerpetualPowersOfTau {
+                    k: *k,
+                    compression: Compression::Uncompressed,
+                };
+                $body
+            }
+            SrsFormat::SnarkJs => {
+                let $reader = &snarkjs::SnarkJs;
+                $body
+            }
+            SrsFormat::AztecIgnition(dir) => {
+                let $reader = &aztec_ignition::AztecIgnition { dir: dir.clone() };
+                $body
+            }
+        }
+    };
+}
+
+mod accumulator;
+mod detect;
+mod error;
+mod format;
+#[cfg(feature = "halo2_proofs")]
+mod params_kzg;
+mod progress;
+mod streaming;
+mod view;
 
 pub mod arithmetic;
 pub mod util;
 
+pub use accumulator::PhaseOneAccumulator;
+pub use detect::{Confidence, Detection};
+pub use error::SrsError;
+pub use format::{SrsReader, SrsWriter};
+pub use progress::{CancellationToken, Phase, Progress, ProgressSink};
+pub use streaming::StreamingSrsWriter;
+pub use view::SrsView;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum SrsFormat {
-    /// From https://github.com/privacy-scaling-explorations/halo2
+    /// From https://github.com/privacy-scaling-explorations/halo2, in
+    /// `SerdeFormat::Processed`.
     Pse,
-    /// From https://github.com/privacy-scaling-explorations/halo2
+    /// From https://github.com/privacy-scaling-explorations/halo2, in
+    /// `SerdeFormat::RawBytes`.
     PseRaw,
-    /// From https://github.com/weijiekoh/perpetualpowersoftau
+    /// From https://github.com/privacy-scaling-explorations/halo2, in
+    /// `SerdeFormat::RawBytesUnchecked`, which has the same layout as
+    /// [`SrsFormat::PseRaw`] but is read without any check, so it's only
+    /// meant for trusted files.
+    PseRawUnchecked,
+    /// From https://github.com/weijiekoh/perpetualpowersoftau, with `k` of the
+    /// whole ceremony, for compressed responses.
     PerpetualPowerOfTau(u32),
+    /// From https://github.com/weijiekoh/perpetualpowersoftau, with `k` of the
+    /// whole ceremony, for challenges and uncompressed responses.
+    PerpetualPowerOfTauUncompressed(u32),
     /// From https://github.com/iden3/snarkjs
     SnarkJs,
+    /// From https://github.com/AztecProtocol/Setup, with the directory
+    /// containing `transcript00.dat` to `transcriptNN.dat`.
+    AztecIgnition(PathBuf),
 }
 
 #[derive(Clone, Debug, Eq)]
@@ -27,6 +120,26 @@ pub struct Srs<M: MultiMillerLoop> {
     pub s_g2: M::G2Affine,
 }
 
+/// Proof of knowledge of the secret `x` of a contribution by
+/// [`Srs::contribute`], where `s_x_g2` is `x` times a point derived from hash
+/// of the SRS before and `(s_g1, s_x_g1)`.
+#[derive(Clone, Debug)]
+pub struct ContributionProof<M: MultiMillerLoop> {
+    pub s_g1: M::G1Affine,
+    pub s_x_g1: M::G1Affine,
+    pub s_x_g2: M::G2Affine,
+}
+
+/// Generators found by [`Srs::verify`], and whether they are the canonical
+/// ones of the curve.
+#[derive(Clone, Debug)]
+pub struct SrsReport<M: MultiMillerLoop> {
+    pub g1_generator: M::G1Affine,
+    pub g2_generator: M::G2Affine,
+    pub is_canonical_g1_generator: bool,
+    pub is_canonical_g2_generator: bool,
+}
+
 impl<M: MultiMillerLoop> PartialEq for Srs<M> {
     fn eq(&self, other: &Self) -> bool {
         (&self.k, &self.g, &self.g_lagrange, &self.g2, &self.s_g2).eq(&(
@@ -45,13 +158,7 @@ where
     M::G2Affine: SerdeObject,
 {
     pub fn read<R: io::Read + io::Seek>(reader: &mut R, format: SrsFormat) -> Self {
-        let desired_k = match format {
-            SrsFormat::Pse | SrsFormat::PseRaw => reader.read_u32::<LittleEndian>().unwrap(),
-            SrsFormat::PerpetualPowerOfTau(k) => k,
-            SrsFormat::SnarkJs => snarkjs::read_k(reader),
-        };
-        reader.rewind().unwrap();
-        Self::read_partial(reader, format, desired_k)
+        Self::try_read(reader, format).unwrap()
     }
 
     pub fn read_partial<R: io::Read + io::Seek>(
@@ -59,80 +166,173 @@ where
         format: SrsFormat,
         desired_k: u32,
     ) -> Self {
-        let srs = match format {
-            SrsFormat::Pse => Self::read_partial_pse::<_, false>(reader, desired_k),
-            SrsFormat::PseRaw => Self::read_partial_pse::<_, true>(reader, desired_k),
-            SrsFormat::PerpetualPowerOfTau(k) => {
-                assert!(desired_k <= k);
+        Self::try_read_partial(reader, format, desired_k).unwrap()
+    }
 
-                let n = 1 << desired_k;
+    pub fn read_auto<R: io::Read + io::Seek>(reader: &mut R) -> Self {
+        Self::try_read_auto(reader).unwrap()
+    }
 
-                let g = perpetual_powers_of_tau::read_g1s::<M, _, false>(reader, n);
-                let g_lagrange = g_to_lagrange(&g, desired_k);
+    /// Reads the SRS in the format detected by [`SrsFormat::detect`].
+    pub fn try_read_auto<R: io::Read + io::Seek>(reader: &mut R) -> Result<Self, SrsError> {
+        let detection = SrsFormat::detect::<M, _>(reader)?;
+        reader.rewind()?;
+        Self::try_read_partial(reader, detection.format, detection.k)
+    }
 
-                let [g2, s_g2]: [_; 2] =
-                    perpetual_powers_of_tau::read_g2s::<M, _, false>(reader, k, 2)
-                        .try_into()
-                        .unwrap();
+    pub fn try_read<R: io::Read + io::Seek>(
+        reader: &mut R,
+        format: SrsFormat,
+    ) -> Result<Self, SrsError> {
+        Self::try_read_with_progress(reader, format, None, &Progress::default())
+    }
 
-                Self {
-                    k: desired_k,
-                    g,
-                    g_lagrange,
-                    g2,
-                    s_g2,
-                }
-            }
-            SrsFormat::SnarkJs => {
-                let k = snarkjs::read_k(reader);
-                assert!(desired_k <= k);
+    pub fn try_read_partial<R: io::Read + io::Seek>(
+        reader: &mut R,
+        format: SrsFormat,
+        desired_k: u32,
+    ) -> Result<Self, SrsError> {
+        Self::try_read_with_progress(reader, format, Some(desired_k), &Progress::default())
+    }
+
+    /// Reads the SRS with `desired_k` or the largest `k` available, reporting
+    /// to `progress` and checking its cancellation between chunks of points.
+    pub fn try_read_with_progress<R: io::Read + io::Seek>(
+        reader: &mut R,
+        format: SrsFormat,
+        desired_k: Option<u32>,
+        progress: &Progress,
+    ) -> Result<Self, SrsError> {
+        match format {
+            SrsFormat::PseRawUnchecked => Self::try_read_k_unchecked(reader, desired_k, progress),
+            format => with_reader!(&format, format => {
+                Self::try_read_k_with(reader, format, desired_k, progress)
+            }),
+        }
+    }
 
-                let n = 1 << desired_k;
+    pub fn read_with<R: io::Read + io::Seek>(reader: &mut R, format: &impl SrsReader<M>) -> Self {
+        Self::try_read_with(reader, format).unwrap()
+    }
 
-                let g = snarkjs::read_g1s::<M, _, false>(reader, n);
-                let g_lagrange = g_to_lagrange(&g, desired_k);
+    pub fn read_partial_with<R: io::Read + io::Seek>(
+        reader: &mut R,
+        format: &impl SrsReader<M>,
+        desired_k: u32,
+    ) -> Self {
+        Self::try_read_partial_with(reader, format, desired_k).unwrap()
+    }
 
-                let [g2, s_g2]: [_; 2] = snarkjs::read_g2s::<M, _, false>(reader, 2)
-                    .try_into()
-                    .unwrap();
+    /// Reads the SRS with a [`SrsReader`], which allows formats not covered
+    /// by [`SrsFormat`].
+    pub fn try_read_with<R: io::Read + io::Seek>(
+        reader: &mut R,
+        format: &impl SrsReader<M>,
+    ) -> Result<Self, SrsError> {
+        Self::try_read_k_with(reader, format, None, &Progress::default())
+    }
 
-                Self {
-                    k: desired_k,
-                    g,
-                    g_lagrange,
-                    g2,
-                    s_g2,
+    pub fn try_read_partial_with<R: io::Read + io::Seek>(
+        reader: &mut R,
+        format: &impl SrsReader<M>,
+        desired_k: u32,
+    ) -> Result<Self, SrsError> {
+        Self::try_read_k_with(reader, format, Some(desired_k), &Progress::default())
+    }
+
+    fn try_read_k_with<R: io::Read + io::Seek>(
+        reader: &mut R,
+        format: &impl SrsReader<M>,
+        desired_k: Option<u32>,
+        progress: &Progress,
+    ) -> Result<Self, SrsError> {
+        let k = format.read_k(reader)?;
+        let desired_k = desired_k.unwrap_or(k);
+        check_desired_k(desired_k, k)?;
+
+        let g = read_g1s_in_chunks(reader, format, 1 << desired_k, progress)?;
+        let g_lagrange = match format.read_g1s_lagrange(reader, desired_k)? {
+            Some(g_lagrange) => {
+                if g_lagrange.len() != g.len() {
+                    return Err(SrsError::ValidationFailed("number of points"));
                 }
+                if !same_lagrange(&g, &g_lagrange) {
+                    return Err(SrsError::ValidationFailed("same lagrange"));
+                }
+                g_lagrange
             }
+            None => g_to_lagrange_with_progress(&g, desired_k, progress)?,
         };
 
-        assert!(srs.validate());
+        let [g2, s_g2] = format.read_g2s(reader)?;
 
-        srs
+        let srs = Self {
+            k: desired_k,
+            g,
+            g_lagrange,
+            g2,
+            s_g2,
+        };
+
+        srs.validate_with_progress(progress)?;
+
+        Ok(srs)
     }
 
-    fn read_partial_pse<R: io::Read + io::Seek, const RAW: bool>(
+    /// Reads [`SrsFormat::PseRawUnchecked`], which skips the on curve check of
+    /// each point, and the consistency checks done by [`Self::try_read_k_with`].
+    fn try_read_k_unchecked<R: io::Read + io::Seek>(
         reader: &mut R,
-        desired_k: u32,
-    ) -> Self {
-        let k = pse::read_k(reader);
-        assert!(desired_k <= k);
+        desired_k: Option<u32>,
+        progress: &Progress,
+    ) -> Result<Self, SrsError> {
+        let format = pse::Pse::<true, false>;
+        let k = SrsReader::<M>::read_k(&format, reader)?;
+        let desired_k = desired_k.unwrap_or(k);
+        check_desired_k(desired_k, k)?;
 
-        let n = 1 << desired_k;
-
-        let g = pse::read_g1s::<M, _, RAW, false>(reader, n);
-        let g_lagrange = if k == desired_k {
-            pse::read_g1s::<M, _, RAW, true>(reader, n)
-        } else {
-            g_to_lagrange(&g, desired_k)
+        let g = read_g1s_in_chunks::<M, _>(reader, &format, 1 << desired_k, progress)?;
+        let g_lagrange = match SrsReader::<M>::read_g1s_lagrange(&format, reader, desired_k)? {
+            Some(g_lagrange) => g_lagrange,
+            None => g_to_lagrange_with_progress(&g, desired_k, progress)?,
         };
+        let [g2, s_g2] = SrsReader::<M>::read_g2s(&format, reader)?;
 
-        let [g2, s_g2]: [_; 2] = pse::read_g2s::<M, _, RAW, false>(reader, 2)
-            .try_into()
-            .unwrap();
+        Ok(Self {
+            k: desired_k,
+            g,
+            g_lagrange,
+            g2,
+            s_g2,
+        })
+    }
+
+    /// Generates SRS from a known `tau`, which is insecure and only meant for
+    /// testing.
+    pub fn setup_insecure(k: u32, tau: M::Scalar) -> Self {
+        let n = 1 << k;
+        let g1 = M::G1Affine::generator();
+
+        let mut g_projective = vec![M::G1::identity(); n];
+        parallelize(&mut g_projective, |g_projective, start| {
+            let mut power = tau.pow_vartime(&[start as u64]);
+            for g_projective in g_projective.iter_mut() {
+                *g_projective = g1 * power;
+                power *= tau;
+            }
+        });
+
+        let mut g = vec![M::G1Affine::identity(); n];
+        parallelize(&mut g, |g, start| {
+            M::G1::batch_normalize(&g_projective[start..start + g.len()], g);
+        });
+        let g_lagrange = g_to_lagrange(&g, k);
+
+        let g2 = M::G2Affine::generator();
+        let s_g2 = (g2 * tau).into();
 
         Self {
-            k: desired_k,
+            k,
             g,
             g_lagrange,
             g2,
@@ -140,28 +340,286 @@ where
         }
     }
 
-    pub fn write(&self, writer: &mut impl io::Write) {
-        writer.write_all(&self.k.to_le_bytes()).unwrap();
+    /// Generates SRS from a `tau` sampled from `rng`, which is insecure since
+    /// `tau` is not discarded properly, and only meant for testing.
+    pub fn setup_from_rng(k: u32, rng: impl RngCore) -> Self {
+        Self::setup_insecure(k, M::Scalar::random(rng))
+    }
+
+    /// Returns BLAKE2b hash of all points, which the proof of a contribution
+    /// on top of this SRS is bound to.
+    pub fn hash(&self) -> [u8; 64] {
+        let mut state = blake2b_simd::State::new();
+        state.update(&self.k.to_le_bytes());
         for point in self.g.iter() {
-            writer.write_all(point.to_bytes().as_ref()).unwrap();
+            point.write_raw(&mut state).unwrap();
+        }
+        self.g2.write_raw(&mut state).unwrap();
+        self.s_g2.write_raw(&mut state).unwrap();
+        *state.finalize().as_array()
+    }
+
+    /// Adds a contribution with a secret `x` sampled from `rng`, which scales
+    /// `g[i]` by `x^i` and `s_g2` by `x`, then recomputes `g_lagrange`.
+    ///
+    /// Returns the new SRS and the proof of knowledge of `x`, which can be
+    /// checked by [`Srs::verify_contribution`].
+    pub fn contribute(&self, mut rng: impl RngCore) -> (Self, ContributionProof<M>) {
+        let x = M::Scalar::random(&mut rng);
+        let s = M::Scalar::random(&mut rng);
+        self.contribute_with(x, s)
+    }
+
+    /// Adds a contribution with secrets derived from a random beacon, where
+    /// `beacon_hash` is hashed by BLAKE2b iteratively
+    /// `2^num_iterations_exp` times, which anyone can recompute to check the
+    /// SRS is finalized by the beacon.
+    ///
+    /// Returns [`SrsError::ValidationFailed`] if `num_iterations_exp` is larger
+    /// than 63, which is also the limit of snarkjs.
+    pub fn contribute_with_beacon(
+        &self,
+        beacon_hash: &[u8],
+        num_iterations_exp: u32,
+    ) -> Result<(Self, ContributionProof<M>), SrsError> {
+        if num_iterations_exp > 63 {
+            return Err(SrsError::ValidationFailed("num_iterations_exp at most 63"));
         }
-        for point in self.g_lagrange.iter() {
-            writer.write_all(point.to_bytes().as_ref()).unwrap();
+
+        let mut hash = *blake2b_simd::blake2b(beacon_hash).as_array();
+        for _ in 1..1u64 << num_iterations_exp {
+            hash = *blake2b_simd::blake2b(&hash).as_array();
         }
-        writer.write_all(self.g2.to_bytes().as_ref()).unwrap();
-        writer.write_all(self.s_g2.to_bytes().as_ref()).unwrap();
+        let [x, s] = [0u8, 1].map(|domain| {
+            let mut state = blake2b_simd::State::new();
+            state.update(&[domain]);
+            state.update(&hash);
+            M::Scalar::from_bytes_wide(state.finalize().as_array())
+        });
+        Ok(self.contribute_with(x, s))
+    }
+
+    fn contribute_with(&self, x: M::Scalar, s: M::Scalar) -> (Self, ContributionProof<M>) {
+        let mut g_projective = vec![M::G1::identity(); self.g.len()];
+        parallelize(&mut g_projective, |g_projective, start| {
+            let mut power = x.pow_vartime(&[start as u64]);
+            for (g_projective, g) in g_projective.iter_mut().zip(self.g[start..].iter()) {
+                *g_projective = *g * power;
+                power *= x;
+            }
+        });
+
+        let mut g = vec![M::G1Affine::identity(); self.g.len()];
+        parallelize(&mut g, |g, start| {
+            M::G1::batch_normalize(&g_projective[start..start + g.len()], g);
+        });
+        let g_lagrange = g_to_lagrange(&g, self.k);
+
+        let s_g1 = (M::G1Affine::generator() * s).to_affine();
+        let s_x_g1 = (s_g1 * x).to_affine();
+        let s_g2 = compute_g2_s::<M>(&self.hash(), &s_g1, &s_x_g1, 0);
+        let proof = ContributionProof {
+            s_g1,
+            s_x_g1,
+            s_x_g2: (s_g2 * x).to_affine(),
+        };
+
+        let srs = Self {
+            k: self.k,
+            g,
+            g_lagrange,
+            g2: self.g2,
+            s_g2: (self.s_g2 * x).to_affine(),
+        };
+        (srs, proof)
+    }
+
+    /// Verifies `after` is `before` with a contribution proven by `proof`,
+    /// which checks:
+    ///
+    /// - `k`, `g[0]` and `g2` are unchanged
+    /// - Proof of knowledge of the secret `x` bound to hash of `before`
+    /// - `g[1]` is scaled by the same `x`
+    /// - `after` is well formed by [`Srs::verify`]
+    pub fn verify_contribution(
+        before: &Self,
+        after: &Self,
+        proof: &ContributionProof<M>,
+    ) -> Result<(), SrsError> {
+        if before.k != after.k || before.g[0] != after.g[0] || before.g2 != after.g2 {
+            return Err(SrsError::ValidationFailed("unchanged generators"));
+        }
+
+        let s_g2 = compute_g2_s::<M>(&before.hash(), &proof.s_g1, &proof.s_x_g1, 0);
+        if !same_ratio_pair::<M>((proof.s_g1, proof.s_x_g1), (s_g2, proof.s_x_g2)) {
+            return Err(SrsError::ValidationFailed("proof of knowledge"));
+        }
+        if before.k > 0 && !same_ratio_pair::<M>((before.g[1], after.g[1]), (s_g2, proof.s_x_g2)) {
+            return Err(SrsError::ValidationFailed("update by proof"));
+        }
+
+        after.verify()?;
+        Ok(())
+    }
+
+    pub fn write(&self, writer: &mut impl io::Write) {
+        self.try_write(writer).unwrap()
     }
 
     pub fn write_raw(&self, writer: &mut impl io::Write) {
-        writer.write_all(&self.k.to_le_bytes()).unwrap();
-        for point in self.g.iter() {
-            point.write_raw(writer).unwrap();
+        self.try_write_raw(writer).unwrap()
+    }
+
+    pub fn try_write(&self, writer: &mut impl io::Write) -> Result<(), SrsError> {
+        self.try_write_as(writer, SrsFormat::Pse)
+    }
+
+    pub fn try_write_raw(&self, writer: &mut impl io::Write) -> Result<(), SrsError> {
+        self.try_write_as(writer, SrsFormat::PseRaw)
+    }
+
+    pub fn write_as(&self, writer: &mut impl io::Write, format: SrsFormat) {
+        self.try_write_as(writer, format).unwrap()
+    }
+
+    /// Writes the SRS in `format`. For formats that carry more points than
+    /// the SRS has, the missing ones are written as identity, so the output
+    /// is readable by this crate but not a complete ceremony transcript.
+    pub fn try_write_as(
+        &self,
+        writer: &mut impl io::Write,
+        format: SrsFormat,
+    ) -> Result<(), SrsError> {
+        self.write_downsized(writer, &format, self.k, &self.g_lagrange)
+    }
+
+    /// Writes the SRS downsized to each `k` in `ks` in every format of
+    /// `formats`, without cloning the SRS. The writer of each output is
+    /// created by `create` and handed to `on_written` once finished.
+    ///
+    /// The outputs are written from the largest `k`, and only one downsized
+    /// `g_lagrange` is alive at a time.
+    pub fn write_all_downsized<W: io::Write>(
+        &self,
+        ks: RangeInclusive<u32>,
+        formats: &[SrsFormat],
+        create: impl FnMut(u32, &SrsFormat) -> Result<W, SrsError>,
+        on_written: impl FnMut(u32, &SrsFormat, W) -> Result<(), SrsError>,
+    ) -> Result<(), SrsError> {
+        self.write_all_downsized_with_progress(
+            ks,
+            formats,
+            create,
+            on_written,
+            &Progress::default(),
+        )
+    }
+
+    /// Same as [`Self::write_all_downsized`], but reports each output to
+    /// `progress` by its number of points in `g`, and checks its cancellation
+    /// between them.
+    pub fn write_all_downsized_with_progress<W: io::Write>(
+        &self,
+        ks: RangeInclusive<u32>,
+        formats: &[SrsFormat],
+        mut create: impl FnMut(u32, &SrsFormat) -> Result<W, SrsError>,
+        mut on_written: impl FnMut(u32, &SrsFormat, W) -> Result<(), SrsError>,
+        progress: &Progress,
+    ) -> Result<(), SrsError> {
+        check_desired_k(*ks.end(), self.k)?;
+
+        let total = ks.clone().map(|k| (formats.len() as u64) << k).sum();
+        let mut written = 0;
+        progress.start(Phase::Write, total)?;
+        for k in ks.rev() {
+            let g_lagrange_downsized;
+            let g_lagrange = if k == self.k {
+                &self.g_lagrange
+            } else {
+                g_lagrange_downsized = g_to_lagrange_with_progress(&self.g[..1 << k], k, progress)?;
+                &g_lagrange_downsized
+            };
+
+            for format in formats {
+                let mut writer = create(k, format)?;
+                self.write_downsized(&mut writer, format, k, g_lagrange)?;
+                writer.flush()?;
+                on_written(k, format, writer)?;
+                written += 1 << k;
+                progress.advance(Phase::Write, written, total)?;
+            }
         }
-        for point in self.g_lagrange.iter() {
-            point.write_raw(writer).unwrap();
+        progress.finish(Phase::Write);
+
+        Ok(())
+    }
+
+    pub fn write_with(&self, writer: &mut impl io::Write, format: &impl SrsWriter<M>) {
+        self.try_write_with(writer, format).unwrap()
+    }
+
+    /// Writes the SRS with a [`SrsWriter`], which allows formats not covered
+    /// by [`SrsFormat`].
+    pub fn try_write_with(
+        &self,
+        writer: &mut impl io::Write,
+        format: &impl SrsWriter<M>,
+    ) -> Result<(), SrsError> {
+        format.write(
+            writer,
+            self.k,
+            &self.g,
+            &self.g_lagrange,
+            [self.g2, self.s_g2],
+        )
+    }
+
+    fn write_downsized(
+        &self,
+        writer: &mut impl io::Write,
+        format: &SrsFormat,
+        k: u32,
+        g_lagrange: &[M::G1Affine],
+    ) -> Result<(), SrsError> {
+        let g = &self.g[..1 << k];
+        let g2s = [self.g2, self.s_g2];
+        match format {
+            SrsFormat::Pse => {
+                SrsWriter::<M>::write(&pse::Pse::<false>, writer, k, g, g_lagrange, g2s)
+            }
+            SrsFormat::PseRaw => {
+                SrsWriter::<M>::write(&pse::Pse::<true>, writer, k, g, g_lagrange, g2s)
+            }
+            SrsFormat::PseRawUnchecked => {
+                SrsWriter::<M>::write(&pse::Pse::<true, false>, writer, k, g, g_lagrange, g2s)
+            }
+            SrsFormat::PerpetualPowerOfTau(k_ceremony) => SrsWriter::<M>::write(
+                &perpetual_powers_of_tau::PerpetualPowersOfTau {
+                    k: *k_ceremony,
+                    compression: Compression::Compressed,
+                },
+                writer,
+                k,
+                g,
+                g_lagrange,
+                g2s,
+            ),
+            SrsFormat::PerpetualPowerOfTauUncompressed(k_ceremony) => SrsWriter::<M>::write(
+                &perpetual_powers_of_tau::PerpetualPowersOfTau {
+                    k: *k_ceremony,
+                    compression: Compression::Uncompressed,
+                },
+                writer,
+                k,
+                g,
+                g_lagrange,
+                g2s,
+            ),
+            SrsFormat::SnarkJs => {
+                SrsWriter::<M>::write(&snarkjs::SnarkJs, writer, k, g, g_lagrange, g2s)
+            }
+            SrsFormat::AztecIgnition(_) => Err(SrsError::UnsupportedFormat("Aztec Ignition")),
         }
-        self.g2.write_raw(writer).unwrap();
-        self.s_g2.write_raw(writer).unwrap();
     }
 
     pub fn downsize(&mut self, k: u32) {
@@ -181,16 +639,236 @@ where
     fn validate(&self) -> bool {
         same_ratio::<M>(&self.g, self.g2, self.s_g2)
     }
+
+    /// Same as [`Self::validate`], but reports to `progress` and checks its
+    /// cancellation between chunks of the MSMs, and fails if it's invalid.
+    fn validate_with_progress(&self, progress: &Progress) -> Result<(), SrsError> {
+        let total = self.g.len() as u64 - 1;
+        let mut processed = 0;
+        progress.start(Phase::Validate, total)?;
+        let valid =
+            same_ratio_in_chunks::<M>(&self.g, self.g2, self.s_g2, READ_CHUNK_SIZE, |points| {
+                processed += points as u64;
+                progress.advance(Phase::Validate, processed, total)
+            })?;
+        progress.finish(Phase::Validate);
+        if !valid {
+            return Err(SrsError::ValidationFailed("same ratio"));
+        }
+        Ok(())
+    }
+
+    /// Verifies the structure of the SRS, which includes:
+    ///
+    /// - `g` and `g_lagrange` both have `2^k` points
+    /// - No point is the identity
+    /// - `s_g2` is consistent with `g[1]`
+    /// - `g` are successive powers by [`same_ratio`]
+    /// - `g_lagrange` is the inverse FFT of `g` by [`same_lagrange`]
+    ///
+    /// Generators are allowed to be non-canonical, which is reported instead.
+    pub fn verify(&self) -> Result<SrsReport<M>, SrsError> {
+        let n = 1 << self.k;
+        if self.g.len() != n || self.g_lagrange.len() != n {
+            return Err(SrsError::ValidationFailed("number of points"));
+        }
+
+        if self
+            .g
+            .par_iter()
+            .chain(self.g_lagrange.par_iter())
+            .any(|point| bool::from(point.is_identity()))
+            || bool::from(self.g2.is_identity() | self.s_g2.is_identity())
+        {
+            return Err(SrsError::ValidationFailed("no identity"));
+        }
+
+        if n > 1 {
+            let is_consistent = M::multi_miller_loop(&[
+                (&self.g[1], &self.g2.into()),
+                (&-self.g[0], &self.s_g2.into()),
+            ])
+            .final_exponentiation()
+            .is_identity();
+            if !bool::from(is_consistent) {
+                return Err(SrsError::ValidationFailed("s_g2 consistent with g[1]"));
+            }
+        }
+
+        if !self.validate() {
+            return Err(SrsError::ValidationFailed("same ratio"));
+        }
+
+        if !same_lagrange(&self.g, &self.g_lagrange) {
+            return Err(SrsError::ValidationFailed("same lagrange"));
+        }
+
+        Ok(SrsReport {
+            g1_generator: self.g[0],
+            g2_generator: self.g2,
+            is_canonical_g1_generator: self.g[0] == M::G1Affine::generator(),
+            is_canonical_g2_generator: self.g2 == M::G2Affine::generator(),
+        })
+    }
+}
+
+/// Reads with the [`SrsReader`] of each [`SrsFormat`], which reads
+/// [`SrsFormat::PseRawUnchecked`] without the on curve check.
+impl<M: MultiMillerLoop> SrsReader<M> for SrsFormat
+where
+    M::G1Affine: SerdeObject,
+    M::G2Affine: SerdeObject,
+{
+    fn read_k<R: io::Read + io::Seek>(&self, reader: &mut R) -> Result<u32, SrsError> {
+        with_reader!(self, format => SrsReader::<M>::read_k(format, reader))
+    }
+
+    fn read_g1s<R: io::Read + io::Seek>(
+        &self,
+        reader: &mut R,
+        range: Range<usize>,
+    ) -> Result<Vec<M::G1Affine>, SrsError> {
+        with_reader!(self, format => SrsReader::<M>::read_g1s(format, reader, range))
+    }
+
+    fn read_g1s_lagrange<R: io::Read + io::Seek>(
+        &self,
+        reader: &mut R,
+        k: u32,
+    ) -> Result<Option<Vec<M::G1Affine>>, SrsError> {
+        with_reader!(self, format => SrsReader::<M>::read_g1s_lagrange(format, reader, k))
+    }
+
+    fn read_g2s<R: io::Read + io::Seek>(
+        &self,
+        reader: &mut R,
+    ) -> Result<[M::G2Affine; 2], SrsError> {
+        with_reader!(self, format => SrsReader::<M>::read_g2s(format, reader))
+    }
+}
+
+/// Number of points read or validated at once, between which progress is
+/// reported.
+const READ_CHUNK_SIZE: usize = 1 << 20;
+
+fn read_g1s_in_chunks<M: MultiMillerLoop, R: io::Read + io::Seek>(
+    reader: &mut R,
+    format: &impl SrsReader<M>,
+    n: usize,
+    progress: &Progress,
+) -> Result<Vec<M::G1Affine>, SrsError> {
+    progress.start(Phase::Read, n as u64)?;
+    let mut g = Vec::with_capacity(n);
+    for start in (0..n).step_by(READ_CHUNK_SIZE) {
+        g.extend(format.read_g1s(reader, start..(start + READ_CHUNK_SIZE).min(n))?);
+        progress.advance(Phase::Read, g.len() as u64, n as u64)?;
+    }
+    progress.finish(Phase::Read);
+    Ok(g)
+}
+
+fn g_to_lagrange_with_progress<C: CurveAffine>(
+    g: &[C],
+    k: u32,
+    progress: &Progress,
+) -> Result<Vec<C>, SrsError> {
+    let total = k as u64 * g.len() as u64;
+    let mut processed = 0;
+    progress.start(Phase::Lagrange, total)?;
+    let g_lagrange = g_to_lagrange_in_rounds(g, k, |points| {
+        processed += points as u64;
+        progress.advance(Phase::Lagrange, processed, total)
+    })?;
+    progress.finish(Phase::Lagrange);
+    Ok(g_lagrange)
+}
+
+pub(crate) fn check_desired_k(desired_k: u32, k: u32) -> Result<(), SrsError> {
+    if desired_k > k {
+        return Err(SrsError::RequestedKTooLarge {
+            requested: desired_k,
+            available: k,
+        });
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod test {
-    use super::{Srs, SrsFormat};
-    use halo2_curves::bn256::Bn256;
-    use std::{fs::File, io::Cursor};
+    use super::{
+        CancellationToken, Confidence, Detection, Phase, Progress, ProgressSink, Srs, SrsError,
+        SrsFormat, SrsReader, SrsView, SrsWriter,
+    };
+    use crate::{
+        arithmetic::g_to_lagrange,
+        util::{aztec_ignition, perpetual_powers_of_tau, pse, snarkjs, zcash, Compression},
+    };
+    use halo2_curves::{
+        bn256::{Bn256, Fr, G1Affine, G2Affine},
+        group::{
+            ff::{Field, PrimeField},
+            Curve,
+        },
+        serde::SerdeObject,
+        CurveAffine,
+    };
+    use rand_core::OsRng;
+    use std::{
+        env,
+        fs::{self, File},
+        io::{Cursor, Read, Seek, SeekFrom, Write},
+        ops::Range,
+    };
+
+    /// Returns an accumulator of `2^k` powers with the given secrets.
+    fn insecure_accumulator(
+        k: u32,
+        tau: Fr,
+        alpha: Fr,
+        beta: Fr,
+    ) -> perpetual_powers_of_tau::Accumulator<Bn256> {
+        use halo2_curves::group::prime::PrimeCurveAffine;
+
+        let powers = |scale: Fr, n: usize| {
+            std::iter::successors(Some(scale), |power| Some(*power * tau))
+                .take(n)
+                .collect::<Vec<_>>()
+        };
+        let g1s = |scalars: Vec<Fr>| {
+            scalars
+                .into_iter()
+                .map(|scalar| (G1Affine::generator() * scalar).to_affine())
+                .collect::<Vec<_>>()
+        };
+        let n = 1 << k;
+        perpetual_powers_of_tau::Accumulator {
+            tau_g1: g1s(powers(Fr::one(), 2 * n - 1)),
+            tau_g2: powers(Fr::one(), n)
+                .into_iter()
+                .map(|scalar| (G2Affine::generator() * scalar).to_affine())
+                .collect(),
+            alpha_tau_g1: g1s(powers(alpha, n)),
+            beta_tau_g1: g1s(powers(beta, n)),
+            beta_g2: (G2Affine::generator() * beta).to_affine(),
+        }
+    }
+
+    /// Returns the proof of knowledge of `x` bound to `digest`, as
+    /// `((s, s * x), g2_s * x)`.
+    fn key_pair(digest: &[u8; 64], x: Fr, personalization: u8) -> ((G1Affine, G1Affine), G2Affine) {
+        use halo2_curves::group::prime::PrimeCurveAffine;
+
+        let s = (G1Affine::generator() * Fr::random(OsRng)).to_affine();
+        let s_x = (s * x).to_affine();
+        let g2_s =
+            perpetual_powers_of_tau::compute_g2_s::<Bn256>(digest, &s, &s_x, personalization);
+        ((s, s_x), (g2_s * x).to_affine())
+    }
 
     #[test]
     fn test_perpetual_powers_of_tau() {
+        use halo2_curves::group::prime::PrimeCurveAffine;
+
         const PATH: &str = "./src/testdata/perpetual-powers-of-tau/bn254-8";
         let from_perpetual_powers_of_tau = Srs::<Bn256>::read(
             &mut File::open(PATH).unwrap(),
@@ -202,6 +880,132 @@ mod test {
             Srs::<Bn256>::read(&mut Cursor::new(buf), SrsFormat::Pse)
         };
         assert_eq!(from_perpetual_powers_of_tau, from_pse);
+
+        // Same layout as the response without the public key appended
+        let mut buf = Vec::new();
+        from_perpetual_powers_of_tau.write_as(&mut buf, SrsFormat::PerpetualPowerOfTau(8));
+        assert_eq!(buf.len() + 768, fs::metadata(PATH).unwrap().len() as usize);
+        assert_eq!(
+            from_perpetual_powers_of_tau,
+            Srs::<Bn256>::read(&mut Cursor::new(&buf), SrsFormat::PerpetualPowerOfTau(8))
+        );
+        // Points are written in the same encoding as the ceremony.
+        let g_size = (1 << 8) * 32;
+        assert_eq!(
+            buf[64..64 + g_size],
+            fs::read(PATH).unwrap()[64..64 + g_size]
+        );
+
+        // Powers beyond `2^k` are padded with identity, which can be read.
+        let mut srs = from_perpetual_powers_of_tau;
+        srs.downsize(3);
+        let mut buf = Vec::new();
+        srs.write_as(&mut buf, SrsFormat::PerpetualPowerOfTau(4));
+        assert_eq!(
+            srs,
+            Srs::<Bn256>::read_partial(
+                &mut Cursor::new(&buf),
+                SrsFormat::PerpetualPowerOfTau(4),
+                3
+            )
+        );
+        let (_, accumulator) = perpetual_powers_of_tau::read_accumulator::<Bn256, _>(
+            &mut Cursor::new(&buf),
+            4,
+            Compression::Compressed,
+        )
+        .unwrap();
+        assert_eq!(accumulator.tau_g1[..1 << 3], srs.g);
+        assert!(accumulator.tau_g1[1 << 3..]
+            .iter()
+            .chain(&accumulator.alpha_tau_g1)
+            .all(|point| bool::from(point.is_identity())));
+    }
+
+    #[test]
+    fn test_perpetual_powers_of_tau_challenge() {
+        use halo2_curves::group::prime::PrimeCurveAffine;
+        use perpetual_powers_of_tau::{
+            read_accumulator, read_public_key, verify_transition, write_accumulator,
+            write_public_key, Accumulator,
+        };
+
+        // The first challenge of the ceremony only contains generators, and
+        // its hash is at the beginning of the response of the first
+        // contribution, so it is the same bytes if the hash matches.
+        const PATH: &str = "./src/testdata/perpetual-powers-of-tau/bn254-8";
+        let n = 1 << 8;
+        let accumulator = Accumulator::<Bn256> {
+            tau_g1: vec![G1Affine::generator(); 2 * n - 1],
+            tau_g2: vec![G2Affine::generator(); n],
+            alpha_tau_g1: vec![G1Affine::generator(); n],
+            beta_tau_g1: vec![G1Affine::generator(); n],
+            beta_g2: G2Affine::generator(),
+        };
+        let mut challenge = Vec::new();
+        write_accumulator(
+            &mut challenge,
+            8,
+            blake2b_simd::blake2b(&[]).as_array(),
+            &accumulator,
+            Compression::Uncompressed,
+        )
+        .unwrap();
+        let response = fs::read(PATH).unwrap();
+        assert_eq!(
+            blake2b_simd::blake2b(&challenge).as_bytes(),
+            &response[..64]
+        );
+        assert_eq!(
+            read_accumulator::<Bn256, _>(
+                &mut Cursor::new(&challenge),
+                8,
+                Compression::Uncompressed
+            )
+            .unwrap()
+            .1,
+            accumulator
+        );
+
+        // Public key is written uncompressed, where some `x` have the bit of
+        // the sign flag of BLS12-381 set.
+        assert!([0, 64, 128, 192, 256, 320]
+            .into_iter()
+            .any(|offset| response[response.len() - 768 + offset] & zcash::SIGN_FLAG != 0));
+        let public_key = read_public_key::<Bn256, _>(&mut Cursor::new(&response), 8).unwrap();
+        let mut buf = Vec::new();
+        write_public_key(&mut buf, &public_key).unwrap();
+        assert_eq!(buf, &response[response.len() - 768..]);
+
+        // Proof of knowledge is derived by the ceremony from the hash of the
+        // challenge, but tau_g1 of this response is powers of 2 instead of
+        // the tau of the public key, so only the update is rejected.
+        let (_, after) =
+            read_accumulator::<Bn256, _>(&mut Cursor::new(&response), 8, Compression::Compressed)
+                .unwrap();
+        assert_eq!(
+            after.tau_g1[1],
+            (G1Affine::generator() * Fr::from(2)).to_affine()
+        );
+        assert!(matches!(
+            verify_transition::<Bn256, _, _>(
+                &mut Cursor::new(&challenge),
+                &mut Cursor::new(&response),
+                8,
+            ),
+            Err(SrsError::ValidationFailed("update by public key"))
+        ));
+        let mut tampered = response.clone();
+        let tau_g2_offset = response.len() - 3 * 128;
+        tampered.copy_within(tau_g2_offset + 128..tau_g2_offset + 256, tau_g2_offset);
+        assert!(matches!(
+            verify_transition::<Bn256, _, _>(
+                &mut Cursor::new(&challenge),
+                &mut Cursor::new(&tampered),
+                8,
+            ),
+            Err(SrsError::ValidationFailed("proof of knowledge"))
+        ));
     }
 
     #[test]
@@ -214,5 +1018,1285 @@ mod test {
             Srs::<Bn256>::read(&mut Cursor::new(buf), SrsFormat::Pse)
         };
         assert_eq!(from_snarkjs, from_pse);
+
+        let mut buf = Vec::new();
+        from_snarkjs.write_as(&mut buf, SrsFormat::SnarkJs);
+        let mut reader = Cursor::new(buf);
+        let header = snarkjs::read_header::<Bn256, _>(&mut reader).unwrap();
+        assert_eq!(header.power, 8);
+        assert_eq!(header.section(snarkjs::LAGRANGE_G1_SECTION_ID), None);
+        assert_eq!(
+            from_snarkjs,
+            Srs::<Bn256>::read(&mut reader, SrsFormat::SnarkJs)
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        const PATH: &str = "./src/testdata/snarkjs/bn254-8";
+        let mut srs = Srs::<Bn256>::read(&mut File::open(PATH).unwrap(), SrsFormat::SnarkJs);
+        let report = srs.verify().unwrap();
+        assert!(report.is_canonical_g1_generator);
+        assert!(report.is_canonical_g2_generator);
+
+        srs.g_lagrange.swap(1, 2);
+        assert!(matches!(
+            srs.verify(),
+            Err(SrsError::ValidationFailed("same lagrange"))
+        ));
+    }
+
+    #[test]
+    fn test_snarkjs_prepared() {
+        const PATH: &str = "./src/testdata/snarkjs/bn254-8";
+        for k in [1, 6, 8] {
+            let srs =
+                Srs::<Bn256>::read_partial(&mut File::open(PATH).unwrap(), SrsFormat::SnarkJs, k);
+            assert_eq!(srs.g_lagrange, g_to_lagrange(&srs.g, k));
+        }
+    }
+
+    #[test]
+    fn test_snarkjs_header() {
+        const PATH: &str = "./src/testdata/snarkjs/bn254-8";
+        let header = snarkjs::read_header::<Bn256, _>(&mut File::open(PATH).unwrap()).unwrap();
+        assert_eq!(header.version, 1);
+        assert_eq!(header.power, 8);
+        assert_eq!(header.ceremony_power, 28);
+        assert_eq!(header.sections.len(), 11);
+        assert_eq!(
+            header.section(snarkjs::TAU_G1_SECTION_ID).unwrap().offset,
+            80
+        );
+
+        let buf = fs::read(PATH).unwrap();
+        for (position, section) in [(0, "magic"), (28, "modulus")] {
+            let mut buf = buf.clone();
+            buf[position] ^= 1;
+            assert!(
+                matches!(
+                    Srs::<Bn256>::try_read(&mut Cursor::new(buf), SrsFormat::SnarkJs),
+                    Err(SrsError::UnexpectedHeader(_))
+                ),
+                "{section}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_view() {
+        const PATH: &str = "./src/testdata/perpetual-powers-of-tau/bn254-8";
+        let srs = Srs::<Bn256>::read(
+            &mut File::open(PATH).unwrap(),
+            SrsFormat::PerpetualPowerOfTau(8),
+        );
+
+        let path = env::temp_dir().join("halo2-kzg-srs-test-view");
+        srs.write_raw(&mut File::create(&path).unwrap());
+        let view = SrsView::<Bn256>::open(&path).unwrap();
+        assert_eq!(view.k(), 8);
+        assert_eq!(view.g(3), srs.g[3]);
+        assert_eq!(view.g_lagrange(5), srs.g_lagrange[5]);
+        assert_eq!((view.g2(), view.s_g2()), (srs.g2, srs.s_g2));
+        assert_eq!(view.to_srs::<true>(8).unwrap(), srs);
+
+        let mut downsized = srs.clone();
+        downsized.downsize(6);
+        assert_eq!(view.to_srs::<false>(6).unwrap(), downsized);
+        assert!(matches!(
+            view.to_srs::<true>(9),
+            Err(SrsError::RequestedKTooLarge { .. })
+        ));
+
+        // A k whose expected length overflows is rejected instead of panicking.
+        fs::write(&path, 62u32.to_le_bytes()).unwrap();
+        assert!(matches!(
+            SrsView::<Bn256>::open(&path),
+            Err(SrsError::UnexpectedHeader(_))
+        ));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_write_all_downsized() {
+        const PATH: &str = "./src/testdata/snarkjs/bn254-8";
+        let srs = Srs::<Bn256>::read(&mut File::open(PATH).unwrap(), SrsFormat::SnarkJs);
+
+        let mut written = Vec::new();
+        srs.write_all_downsized(
+            6..=8,
+            &[SrsFormat::Pse, SrsFormat::PseRaw],
+            |_, _| Ok(Vec::new()),
+            |k, format, buf| {
+                written.push((k, matches!(format, SrsFormat::PseRaw), buf));
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(written.len(), 6);
+        for (k, raw, buf) in written {
+            let mut downsized = srs.clone();
+            downsized.downsize(k);
+            let mut expected = Vec::new();
+            if raw {
+                downsized.write_raw(&mut expected);
+            } else {
+                downsized.write(&mut expected);
+            }
+            assert_eq!(buf, expected);
+        }
+    }
+
+    #[test]
+    fn test_write_out_of_core() {
+        use crate::arithmetic::out_of_core_point_size;
+
+        let srs = Srs::<Bn256>::setup_from_rng(5, OsRng);
+        let mut raw = Vec::new();
+        srs.write_raw(&mut raw);
+        let mut processed = Vec::new();
+        srs.write(&mut processed);
+
+        // Budget of a whole, a single, some and a few of columns or rows
+        for points in [1 << 5, 1, 3, 8, 17] {
+            let memory_budget = points * out_of_core_point_size::<G1Affine>();
+            let read_g = |range: Range<usize>| Ok(srs.g[range].to_vec());
+            let g2s = [srs.g2, srs.s_g2];
+
+            let mut buf = Vec::new();
+            pse::write_out_of_core::<Bn256, _, _, true>(
+                &mut buf,
+                srs.k,
+                read_g,
+                &g2s,
+                &mut Cursor::new(Vec::new()),
+                memory_budget,
+                &Progress::default(),
+            )
+            .unwrap();
+            assert_eq!(buf, raw);
+
+            let mut buf = Vec::new();
+            pse::write_out_of_core::<Bn256, _, _, false>(
+                &mut buf,
+                srs.k,
+                read_g,
+                &g2s,
+                &mut Cursor::new(Vec::new()),
+                memory_budget,
+                &Progress::default(),
+            )
+            .unwrap();
+            assert_eq!(buf, processed);
+        }
+    }
+
+    #[test]
+    fn test_progress() {
+        use halo2_curves::bn256::G1;
+        use std::{
+            mem::size_of,
+            sync::{Arc, Mutex},
+        };
+
+        type Event = (Phase, &'static str, u64, u64);
+
+        #[derive(Clone, Default)]
+        struct Recorder(Arc<Mutex<Vec<Event>>>);
+
+        impl ProgressSink for Recorder {
+            fn started(&self, phase: Phase, total: u64) {
+                self.0.lock().unwrap().push((phase, "started", 0, total));
+            }
+
+            fn advanced(&self, phase: Phase, processed: u64, total: u64) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((phase, "advanced", processed, total));
+            }
+
+            fn finished(&self, phase: Phase) {
+                self.0.lock().unwrap().push((phase, "finished", 0, 0));
+            }
+        }
+
+        let srs = Srs::<Bn256>::setup_from_rng(4, OsRng);
+        let mut raw = Vec::new();
+        srs.write_raw(&mut raw);
+
+        let recorder = Recorder::default();
+        let progress = Progress::new().with_sink(recorder.clone());
+        let read = Srs::<Bn256>::try_read_with_progress(
+            &mut Cursor::new(&raw),
+            SrsFormat::PseRawUnchecked,
+            Some(3),
+            &progress,
+        )
+        .unwrap();
+        assert_eq!(read.g, srs.g[..8]);
+        assert_eq!(read.g_lagrange, g_to_lagrange(&read.g, 3));
+        assert_eq!(
+            recorder.0.lock().unwrap().drain(..).collect::<Vec<_>>(),
+            vec![
+                (Phase::Read, "started", 0, 8),
+                (Phase::Read, "advanced", 8, 8),
+                (Phase::Read, "finished", 0, 0),
+                (Phase::Lagrange, "started", 0, 24),
+                (Phase::Lagrange, "advanced", 8, 24),
+                (Phase::Lagrange, "advanced", 16, 24),
+                (Phase::Lagrange, "advanced", 24, 24),
+                (Phase::Lagrange, "finished", 0, 0),
+            ]
+        );
+
+        // Validation is finished even if it fails.
+        let mut invalid = srs.clone();
+        invalid.g.swap(1, 2);
+        invalid.g_lagrange = g_to_lagrange(&invalid.g, invalid.k);
+        let mut invalid_raw = Vec::new();
+        invalid.write_raw(&mut invalid_raw);
+        assert!(matches!(
+            Srs::<Bn256>::try_read_with_progress(
+                &mut Cursor::new(&invalid_raw),
+                SrsFormat::PseRaw,
+                None,
+                &progress,
+            ),
+            Err(SrsError::ValidationFailed("same ratio"))
+        ));
+        let events = recorder.0.lock().unwrap().drain(..).collect::<Vec<_>>();
+        assert_eq!(
+            events[events.len() - 3..],
+            [
+                (Phase::Validate, "started", 0, 15),
+                (Phase::Validate, "advanced", 15, 15),
+                (Phase::Validate, "finished", 0, 0),
+            ]
+        );
+
+        let mut buf = Vec::new();
+        pse::write_out_of_core::<Bn256, _, _, true>(
+            &mut buf,
+            srs.k,
+            |range: Range<usize>| Ok(srs.g[range].to_vec()),
+            &[srs.g2, srs.s_g2],
+            &mut Cursor::new(Vec::new()),
+            4 * size_of::<G1>(),
+            &progress,
+        )
+        .unwrap();
+        assert_eq!(buf, raw);
+        let events = recorder.0.lock().unwrap().drain(..).collect::<Vec<_>>();
+        assert_eq!(events[0], (Phase::Write, "started", 0, 16));
+        assert!(events.contains(&(Phase::Lagrange, "advanced", 48, 48)));
+        assert_eq!(events.last(), Some(&(Phase::Lagrange, "finished", 0, 0)));
+
+        srs.write_all_downsized_with_progress(
+            3..=4,
+            &[SrsFormat::Pse, SrsFormat::PseRaw],
+            |_, _| Ok(Vec::new()),
+            |_, _, _| Ok(()),
+            &progress,
+        )
+        .unwrap();
+        let events = recorder.0.lock().unwrap().drain(..).collect::<Vec<_>>();
+        let writes = events
+            .into_iter()
+            .filter(|(phase, ..)| *phase == Phase::Write)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            writes,
+            vec![
+                (Phase::Write, "started", 0, 48),
+                (Phase::Write, "advanced", 16, 48),
+                (Phase::Write, "advanced", 32, 48),
+                (Phase::Write, "advanced", 40, 48),
+                (Phase::Write, "advanced", 48, 48),
+                (Phase::Write, "finished", 0, 0),
+            ]
+        );
+
+        let cancellation = CancellationToken::new();
+        let progress = progress.with_cancellation(cancellation.clone());
+        cancellation.cancel();
+        assert!(matches!(
+            Srs::<Bn256>::try_read_with_progress(
+                &mut Cursor::new(&raw),
+                SrsFormat::PseRawUnchecked,
+                None,
+                &progress,
+            ),
+            Err(SrsError::Cancelled)
+        ));
+    }
+
+    #[test]
+    fn test_streaming_srs_writer() {
+        use super::StreamingSrsWriter;
+        use halo2_curves::bn256::G1;
+        use std::mem::size_of;
+
+        let srs = Srs::<Bn256>::setup_from_rng(4, OsRng);
+        let mut raw = Vec::new();
+        srs.write_raw(&mut raw);
+        let mut processed = Vec::new();
+        srs.write(&mut processed);
+
+        for (format, expected) in [(SrsFormat::PseRaw, &raw), (SrsFormat::Pse, &processed)] {
+            let mut writer = StreamingSrsWriter::<Bn256, _, _>::new(
+                Cursor::new(Vec::new()),
+                Cursor::new(Vec::new()),
+                format,
+                3 * size_of::<G1>(),
+            )
+            .unwrap();
+            writer.write_g1s(&srs.g[..5]).unwrap();
+            writer
+                .copy_g1s_from(&mut Cursor::new(&raw), &SrsFormat::PseRaw, 5..16)
+                .unwrap();
+            assert_eq!(writer.len(), 16);
+            let buf = writer.finish([srs.g2, srs.s_g2]).unwrap().into_inner();
+            assert_eq!(&buf, expected);
+        }
+
+        let mut writer = StreamingSrsWriter::<Bn256, _, _>::new(
+            Cursor::new(Vec::new()),
+            Cursor::new(Vec::new()),
+            SrsFormat::PseRaw,
+            1 << 20,
+        )
+        .unwrap();
+        writer.write_g1s(&srs.g[..3]).unwrap();
+        assert!(matches!(
+            writer.finish([srs.g2, srs.s_g2]),
+            Err(SrsError::ValidationFailed("number of g is a power of 2"))
+        ));
+        assert!(matches!(
+            StreamingSrsWriter::<Bn256, _, _>::new(
+                Cursor::new(Vec::new()),
+                Cursor::new(Vec::new()),
+                SrsFormat::SnarkJs,
+                1 << 20,
+            ),
+            Err(SrsError::UnsupportedFormat("streaming writer"))
+        ));
+    }
+
+    #[test]
+    fn test_contribute() {
+        let before = Srs::<Bn256>::setup_from_rng(4, OsRng);
+        let (after, proof) = before.contribute(OsRng);
+        assert_ne!(before, after);
+        Srs::verify_contribution(&before, &after, &proof).unwrap();
+
+        let (finalized, beacon_proof) = after.contribute_with_beacon(b"beacon", 3).unwrap();
+        assert_eq!((finalized.clone(), beacon_proof.s_x_g2), {
+            let (finalized, proof) = after.contribute_with_beacon(b"beacon", 3).unwrap();
+            (finalized, proof.s_x_g2)
+        });
+        Srs::verify_contribution(&after, &finalized, &beacon_proof).unwrap();
+        assert!(matches!(
+            after.contribute_with_beacon(b"beacon", 64),
+            Err(SrsError::ValidationFailed("num_iterations_exp at most 63"))
+        ));
+
+        assert!(matches!(
+            Srs::verify_contribution(&before, &finalized, &proof),
+            Err(SrsError::ValidationFailed("update by proof"))
+        ));
+    }
+
+    #[test]
+    fn test_phase_one_accumulator() {
+        use super::PhaseOneAccumulator;
+        use halo2_curves::group::prime::PrimeCurveAffine;
+        use perpetual_powers_of_tau::Accumulator;
+
+        const K: u32 = 2;
+        let [tau, alpha, beta] = [(); 3].map(|_| Fr::random(OsRng));
+        let mut accumulator = insecure_accumulator(K, tau, alpha, beta);
+
+        let mut buf = Vec::new();
+        snarkjs::write_accumulator(&mut buf, K, &accumulator, &[]).unwrap();
+        let phase_one =
+            PhaseOneAccumulator::<Bn256>::read(&mut Cursor::new(&buf), SrsFormat::SnarkJs);
+        assert_eq!(phase_one.tau_g1, accumulator.tau_g1);
+        assert_eq!(
+            phase_one.alpha_tau_g1.as_ref(),
+            Some(&accumulator.alpha_tau_g1)
+        );
+        assert_eq!(
+            phase_one.beta_tau_g1.as_ref(),
+            Some(&accumulator.beta_tau_g1)
+        );
+        assert_eq!(phase_one.beta_g2, Some(accumulator.beta_g2));
+        assert_eq!(Srs::from(phase_one), Srs::<Bn256>::setup_insecure(K, tau));
+
+        let phase_one = PhaseOneAccumulator::<Bn256>::read_partial(
+            &mut Cursor::new(&buf),
+            SrsFormat::SnarkJs,
+            1,
+        );
+        assert_eq!(phase_one.tau_g1, accumulator.tau_g1[..3]);
+        assert_eq!(
+            phase_one.beta_tau_g1.as_ref().unwrap(),
+            &accumulator.beta_tau_g1[..2]
+        );
+        assert_eq!(Srs::from(phase_one), Srs::<Bn256>::setup_insecure(1, tau));
+
+        let mut buf = Vec::new();
+        perpetual_powers_of_tau::write_accumulator(
+            &mut buf,
+            K,
+            &[0; 64],
+            &accumulator,
+            Compression::Uncompressed,
+        )
+        .unwrap();
+        let phase_one = PhaseOneAccumulator::<Bn256>::read(
+            &mut Cursor::new(&buf),
+            SrsFormat::PerpetualPowerOfTauUncompressed(K),
+        );
+        assert_eq!(
+            phase_one.alpha_tau_g1.as_ref(),
+            Some(&accumulator.alpha_tau_g1)
+        );
+
+        accumulator.alpha_tau_g1 = vec![G1Affine::identity(); 1 << K];
+        accumulator.beta_tau_g1 = vec![G1Affine::identity(); 1 << K];
+        accumulator.beta_g2 = G2Affine::identity();
+        let mut buf = Vec::new();
+        snarkjs::write_accumulator(&mut buf, K, &accumulator, &[]).unwrap();
+        let phase_one =
+            PhaseOneAccumulator::<Bn256>::read(&mut Cursor::new(&buf), SrsFormat::SnarkJs);
+        assert!(phase_one.alpha_tau_g1.is_none());
+        assert!(phase_one.beta_tau_g1.is_none());
+        assert!(phase_one.beta_g2.is_none());
+
+        accumulator = insecure_accumulator(K, tau, alpha, beta);
+        accumulator.alpha_tau_g1.swap(1, 2);
+        let mut buf = Vec::new();
+        snarkjs::write_accumulator(&mut buf, K, &accumulator, &[]).unwrap();
+        assert!(matches!(
+            PhaseOneAccumulator::<Bn256>::try_read(&mut Cursor::new(&buf), SrsFormat::SnarkJs),
+            Err(SrsError::ValidationFailed("same ratio"))
+        ));
+        assert!(matches!(
+            PhaseOneAccumulator::<Bn256>::try_read(&mut Cursor::new(&buf), SrsFormat::Pse),
+            Err(SrsError::UnsupportedFormat("PSE"))
+        ));
+
+        // Power 0 has no tauG2 beyond the generator.
+        let mut buf = Vec::new();
+        snarkjs::write_accumulator(&mut buf, 0, &accumulator, &[]).unwrap();
+        assert!(matches!(
+            PhaseOneAccumulator::<Bn256>::try_read(&mut Cursor::new(&buf), SrsFormat::SnarkJs),
+            Err(SrsError::UnexpectedHeader(_))
+        ));
+
+        // Corrupted beta_g2 or tail of tau_g2 is rejected as well.
+        accumulator = insecure_accumulator(K, tau, alpha, beta);
+        let try_read = |accumulator: &Accumulator<Bn256>| {
+            let mut buf = Vec::new();
+            snarkjs::write_accumulator(&mut buf, K, accumulator, &[]).unwrap();
+            PhaseOneAccumulator::<Bn256>::try_read(&mut Cursor::new(&buf), SrsFormat::SnarkJs)
+        };
+        assert!(try_read(&accumulator).is_ok());
+        let mut corrupted = accumulator.clone();
+        corrupted.beta_g2 = (G2Affine::generator() * alpha).to_affine();
+        assert!(matches!(
+            try_read(&corrupted),
+            Err(SrsError::ValidationFailed("same ratio"))
+        ));
+        let mut corrupted = accumulator.clone();
+        corrupted.tau_g2[3] = G2Affine::generator();
+        assert!(matches!(
+            try_read(&corrupted),
+            Err(SrsError::ValidationFailed("same ratio"))
+        ));
+
+        // Power 0 has no tau_g2 beyond the generator.
+        let mut buf = Vec::new();
+        perpetual_powers_of_tau::write_accumulator(
+            &mut buf,
+            0,
+            &[0; 64],
+            &accumulator,
+            Compression::Compressed,
+        )
+        .unwrap();
+        assert!(matches!(
+            PhaseOneAccumulator::<Bn256>::try_read(
+                &mut Cursor::new(&buf),
+                SrsFormat::PerpetualPowerOfTau(0)
+            ),
+            Err(SrsError::UnsupportedFormat(_))
+        ));
+
+        // Response of the first contribution of Perpetual Powers of Tau has
+        // all sections.
+        const PATH: &str = "./src/testdata/perpetual-powers-of-tau/bn254-8";
+        let srs = Srs::<Bn256>::read(
+            &mut File::open(PATH).unwrap(),
+            SrsFormat::PerpetualPowerOfTau(8),
+        );
+        let phase_one = PhaseOneAccumulator::<Bn256>::read(
+            &mut File::open(PATH).unwrap(),
+            SrsFormat::PerpetualPowerOfTau(8),
+        );
+        assert_eq!(phase_one.tau_g1.len(), (2 << 8) - 1);
+        assert_eq!(phase_one.alpha_tau_g1.as_ref().unwrap().len(), 1 << 8);
+        assert_eq!(phase_one.beta_tau_g1.as_ref().unwrap().len(), 1 << 8);
+        assert!(phase_one.beta_g2.is_some());
+        assert_eq!(Srs::from(phase_one), srs);
+        let phase_one = PhaseOneAccumulator::<Bn256>::read_partial(
+            &mut File::open(PATH).unwrap(),
+            SrsFormat::PerpetualPowerOfTau(8),
+            4,
+        );
+        assert_eq!(phase_one.alpha_tau_g1.as_ref().unwrap().len(), 1 << 4);
+        assert_eq!(phase_one.tau_g1, srs.g[..(2 << 4) - 1]);
+
+        // Sections written as identity are read as `None`.
+        let mut buf = Vec::new();
+        srs.write_as(&mut buf, SrsFormat::PerpetualPowerOfTau(8));
+        let phase_one = PhaseOneAccumulator::<Bn256>::read_partial(
+            &mut Cursor::new(&buf),
+            SrsFormat::PerpetualPowerOfTau(8),
+            7,
+        );
+        assert!(phase_one.alpha_tau_g1.is_none());
+        assert!(phase_one.beta_tau_g1.is_none());
+        assert!(phase_one.beta_g2.is_none());
+        assert_eq!(phase_one.tau_g1, srs.g[..(2 << 7) - 1]);
+        assert_eq!(phase_one.tau_g2, [srs.g2, srs.s_g2]);
+    }
+
+    #[cfg(feature = "halo2_proofs")]
+    #[test]
+    fn test_params_kzg() {
+        use halo2_proofs::{
+            circuit::{Layouter, SimpleFloorPlanner, Value},
+            plonk::{
+                create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column,
+                ConstraintSystem, Error, Instance, Selector,
+            },
+            poly::{
+                commitment::ParamsProver,
+                kzg::{
+                    commitment::{KZGCommitmentScheme, ParamsKZG},
+                    multiopen::{ProverSHPLONK, VerifierSHPLONK},
+                    strategy::SingleStrategy,
+                },
+                Rotation,
+            },
+            transcript::{
+                Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer,
+                TranscriptWriterBuffer,
+            },
+            SerdeFormat,
+        };
+
+        #[derive(Clone)]
+        struct Square(Value<Fr>);
+
+        impl Circuit<Fr> for Square {
+            type Config = (Column<Advice>, Column<Instance>, Selector);
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                Self(Value::unknown())
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+                let advice = meta.advice_column();
+                let instance = meta.instance_column();
+                let selector = meta.selector();
+                meta.enable_equality(advice);
+                meta.enable_equality(instance);
+                meta.create_gate("square", |meta| {
+                    let selector = meta.query_selector(selector);
+                    let x = meta.query_advice(advice, Rotation::cur());
+                    let y = meta.query_advice(advice, Rotation::next());
+                    vec![selector * (x.clone() * x - y)]
+                });
+                (advice, instance, selector)
+            }
+
+            fn synthesize(
+                &self,
+                (advice, instance, selector): Self::Config,
+                mut layouter: impl Layouter<Fr>,
+            ) -> Result<(), Error> {
+                let y = layouter.assign_region(
+                    || "square",
+                    |mut region| {
+                        selector.enable(&mut region, 0)?;
+                        region.assign_advice(|| "x", advice, 0, || self.0)?;
+                        region.assign_advice(|| "y", advice, 1, || self.0.map(|x| x.square()))
+                    },
+                )?;
+                layouter.constrain_instance(y.cell(), instance, 0)
+            }
+        }
+
+        let srs = Srs::<Bn256>::setup_from_rng(5, OsRng);
+        let params = ParamsKZG::<Bn256>::from(srs.clone());
+        assert_eq!(Srs::from(params.clone()), srs);
+
+        for (serde_format, format) in [
+            (SerdeFormat::Processed, SrsFormat::Pse),
+            (SerdeFormat::RawBytes, SrsFormat::PseRaw),
+            (SerdeFormat::RawBytesUnchecked, SrsFormat::PseRawUnchecked),
+        ] {
+            let mut buf = Vec::new();
+            params.write_custom(&mut buf, serde_format).unwrap();
+            assert_eq!(Srs::read(&mut Cursor::new(&buf), format.clone()), srs);
+
+            let mut buf = Vec::new();
+            srs.write_as(&mut buf, format);
+            let read = ParamsKZG::<Bn256>::read_custom(&mut buf.as_slice(), serde_format).unwrap();
+            assert_eq!(Srs::from(read), srs);
+        }
+
+        let circuit = Square(Value::known(Fr::from(3)));
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let pk = keygen_pk(&params, vk, &circuit).unwrap();
+        let verify = |instance: Fr| {
+            let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+            create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<_>, _, _, _, _>(
+                &params,
+                &pk,
+                &[circuit.clone()],
+                &[&[&[instance]]],
+                OsRng,
+                &mut transcript,
+            )
+            .unwrap();
+            let proof = transcript.finalize();
+
+            let mut transcript =
+                Blake2bRead::<_, G1Affine, Challenge255<_>>::init(proof.as_slice());
+            verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<_>, _, _, _>(
+                params.verifier_params(),
+                pk.get_vk(),
+                SingleStrategy::new(&params),
+                &[&[&[instance]]],
+                &mut transcript,
+            )
+            .is_ok()
+        };
+        assert!(verify(Fr::from(9)));
+        assert!(!verify(Fr::from(10)));
+    }
+
+    #[test]
+    fn test_setup_insecure() {
+        let tau = Fr::from(42);
+        let srs = Srs::<Bn256>::setup_insecure(6, tau);
+        assert_eq!(srs.g[1], (srs.g[0] * tau).to_affine());
+        assert_eq!(srs.g[63], (srs.g[0] * tau.pow_vartime(&[63])).to_affine());
+        assert_eq!(srs.s_g2, (srs.g2 * tau).to_affine());
+        srs.verify().unwrap();
+
+        let mut buf = Vec::new();
+        srs.write_as(&mut buf, SrsFormat::SnarkJs);
+        assert_eq!(
+            srs,
+            Srs::<Bn256>::read(&mut Cursor::new(buf), SrsFormat::SnarkJs)
+        );
+
+        Srs::<Bn256>::setup_from_rng(4, OsRng).verify().unwrap();
+    }
+
+    #[test]
+    fn test_detect() {
+        const PPOT_PATH: &str = "./src/testdata/perpetual-powers-of-tau/bn254-8";
+        const SNARKJS_PATH: &str = "./src/testdata/snarkjs/bn254-8";
+        let detect = |buf: Vec<u8>| SrsFormat::detect::<Bn256, _>(&mut Cursor::new(buf)).unwrap();
+
+        assert_eq!(
+            detect(fs::read(SNARKJS_PATH).unwrap()),
+            Detection {
+                format: SrsFormat::SnarkJs,
+                k: 8,
+                confidence: Confidence::High
+            }
+        );
+        assert_eq!(
+            detect(fs::read(PPOT_PATH).unwrap()),
+            Detection {
+                format: SrsFormat::PerpetualPowerOfTau(8),
+                k: 8,
+                confidence: Confidence::Medium
+            }
+        );
+
+        let srs = Srs::<Bn256>::read_auto(&mut File::open(PPOT_PATH).unwrap());
+        for format in [
+            SrsFormat::Pse,
+            SrsFormat::PseRaw,
+            SrsFormat::PerpetualPowerOfTau(8),
+        ] {
+            let mut buf = Vec::new();
+            srs.write_as(&mut buf, format.clone());
+            assert_eq!(detect(buf.clone()).format, format);
+            assert_eq!(Srs::<Bn256>::read_auto(&mut Cursor::new(buf)), srs);
+        }
+
+        assert!(matches!(
+            SrsFormat::detect::<Bn256, _>(&mut Cursor::new(vec![0; 10])),
+            Err(SrsError::UnexpectedHeader(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_write_with() {
+        // Format with `g` and `[g2, s_g2]` only, in raw format
+        struct Minimal;
+
+        impl SrsReader<Bn256> for Minimal {
+            fn read_k<R: Read + Seek>(&self, reader: &mut R) -> Result<u32, SrsError> {
+                reader.rewind()?;
+                let mut k = [0; 4];
+                reader.read_exact(&mut k)?;
+                Ok(u32::from_le_bytes(k))
+            }
+
+            fn read_g1s<R: Read + Seek>(
+                &self,
+                reader: &mut R,
+                range: Range<usize>,
+            ) -> Result<Vec<G1Affine>, SrsError> {
+                reader.seek(SeekFrom::Start(4 + 64 * range.start as u64))?;
+                range.map(|_| Ok(G1Affine::read_raw(reader)?)).collect()
+            }
+
+            fn read_g2s<R: Read + Seek>(&self, reader: &mut R) -> Result<[G2Affine; 2], SrsError> {
+                let k = self.read_k(reader)?;
+                reader.seek(SeekFrom::Start(4 + (64 << k)))?;
+                Ok([G2Affine::read_raw(reader)?, G2Affine::read_raw(reader)?])
+            }
+        }
+
+        impl SrsWriter<Bn256> for Minimal {
+            fn write<W: Write>(
+                &self,
+                writer: &mut W,
+                k: u32,
+                g: &[G1Affine],
+                _: &[G1Affine],
+                g2s: [G2Affine; 2],
+            ) -> Result<(), SrsError> {
+                writer.write_all(&k.to_le_bytes())?;
+                for point in g {
+                    point.write_raw(writer)?;
+                }
+                for point in g2s {
+                    point.write_raw(writer)?;
+                }
+                Ok(())
+            }
+        }
+
+        let srs = Srs::<Bn256>::setup_insecure(5, Fr::from(7));
+        let mut buf = Vec::new();
+        srs.write_with(&mut buf, &Minimal);
+        assert_eq!(buf.len(), 4 + 64 * 32 + 128 * 2);
+        assert_eq!(Srs::read_with(&mut Cursor::new(&buf), &Minimal), srs);
+
+        let mut downsized = srs.clone();
+        downsized.downsize(3);
+        assert_eq!(
+            Srs::read_partial_with(&mut Cursor::new(&buf), &Minimal, 3),
+            downsized
+        );
+    }
+
+    #[test]
+    fn test_try_read_errors() {
+        const PATH: &str = "./src/testdata/snarkjs/bn254-8";
+        assert!(matches!(
+            Srs::<Bn256>::try_read_partial(&mut File::open(PATH).unwrap(), SrsFormat::SnarkJs, 9),
+            Err(SrsError::RequestedKTooLarge {
+                requested: 9,
+                available: 8
+            })
+        ));
+
+        let mut buf = Vec::new();
+        Srs::<Bn256>::read(&mut File::open(PATH).unwrap(), SrsFormat::SnarkJs).write(&mut buf);
+        buf.truncate(buf.len() / 2);
+        assert!(matches!(
+            Srs::<Bn256>::try_read(&mut Cursor::new(buf), SrsFormat::Pse),
+            Err(SrsError::Io(_))
+        ));
+
+        // Format with one point missing in `g_lagrange`
+        struct ShortLagrange;
+
+        impl SrsReader<Bn256> for ShortLagrange {
+            fn read_k<R: Read + Seek>(&self, reader: &mut R) -> Result<u32, SrsError> {
+                SrsReader::<Bn256>::read_k(&SrsFormat::Pse, reader)
+            }
+
+            fn read_g1s<R: Read + Seek>(
+                &self,
+                reader: &mut R,
+                range: Range<usize>,
+            ) -> Result<Vec<G1Affine>, SrsError> {
+                SrsReader::<Bn256>::read_g1s(&SrsFormat::Pse, reader, range)
+            }
+
+            fn read_g1s_lagrange<R: Read + Seek>(
+                &self,
+                reader: &mut R,
+                k: u32,
+            ) -> Result<Option<Vec<G1Affine>>, SrsError> {
+                let g_lagrange = SrsReader::<Bn256>::read_g1s_lagrange(&SrsFormat::Pse, reader, k)?;
+                Ok(g_lagrange.map(|g_lagrange| g_lagrange[1..].to_vec()))
+            }
+
+            fn read_g2s<R: Read + Seek>(&self, reader: &mut R) -> Result<[G2Affine; 2], SrsError> {
+                SrsReader::<Bn256>::read_g2s(&SrsFormat::Pse, reader)
+            }
+        }
+
+        let mut buf = Vec::new();
+        Srs::<Bn256>::setup_insecure(3, Fr::from(7)).write(&mut buf);
+        assert!(matches!(
+            Srs::try_read_with(&mut Cursor::new(buf), &ShortLagrange),
+            Err(SrsError::ValidationFailed("number of points"))
+        ));
+    }
+
+    #[test]
+    fn test_pse_raw_unchecked() {
+        let srs = Srs::<Bn256>::setup_from_rng(4, OsRng);
+        let mut raw = Vec::new();
+        srs.write_raw(&mut raw);
+        let mut buf = Vec::new();
+        srs.write_as(&mut buf, SrsFormat::PseRawUnchecked);
+        assert_eq!(buf, raw);
+
+        assert_eq!(
+            Srs::<Bn256>::read(&mut Cursor::new(&raw), SrsFormat::PseRawUnchecked),
+            srs
+        );
+        assert_eq!(
+            Srs::<Bn256>::read_partial(&mut Cursor::new(&raw), SrsFormat::PseRawUnchecked, 2),
+            Srs::<Bn256>::read_partial(&mut Cursor::new(&raw), SrsFormat::PseRaw, 2),
+        );
+
+        // Corrupt `x` of `g[1]` to be off curve
+        raw[4 + 64] ^= 1;
+        assert!(matches!(
+            Srs::<Bn256>::try_read(&mut Cursor::new(&raw), SrsFormat::PseRaw),
+            Err(SrsError::NotOnCurve {
+                index: 1,
+                section: "g1"
+            })
+        ));
+        let unchecked = Srs::<Bn256>::read(&mut Cursor::new(&raw), SrsFormat::PseRawUnchecked);
+        assert_ne!(unchecked.g[1], srs.g[1]);
+        assert_eq!(unchecked.g_lagrange, srs.g_lagrange);
+    }
+
+    #[test]
+    fn test_aztec_ignition() {
+        fn write_field_element<F: PrimeField>(writer: &mut impl Write, fe: &F) {
+            let mut repr = fe.to_repr();
+            for limb in repr.as_mut().chunks_mut(8) {
+                limb.reverse();
+            }
+            writer.write_all(repr.as_ref()).unwrap();
+        }
+
+        fn write_ec_point<C: CurveAffine>(writer: &mut impl Write, point: &C) {
+            let coordinates = point.coordinates().unwrap();
+            write_field_element(writer, coordinates.x());
+            write_field_element(writer, coordinates.y());
+        }
+
+        const PATH: &str = "./src/testdata/perpetual-powers-of-tau/bn254-8";
+        let from_perpetual_powers_of_tau = Srs::<Bn256>::read(
+            &mut File::open(PATH).unwrap(),
+            SrsFormat::PerpetualPowerOfTau(8),
+        );
+
+        // Split the powers without the generator into 2 transcripts like
+        // Aztec Ignition does, with the G2 point only in the first one.
+        let dir = env::temp_dir().join("halo2-kzg-srs-test-aztec-ignition");
+        fs::create_dir_all(&dir).unwrap();
+        let g1s = &from_perpetual_powers_of_tau.g[1..];
+        for (transcript_number, (start_from, g1s)) in [(0, &g1s[..128]), (128, &g1s[128..])]
+            .into_iter()
+            .enumerate()
+        {
+            let mut file = File::create(aztec_ignition::transcript_path(
+                &dir,
+                transcript_number as u32,
+            ))
+            .unwrap();
+            let num_g2_points = if transcript_number == 0 { 1 } else { 0 };
+            for field in [
+                transcript_number as u32,
+                2,
+                255,
+                1,
+                g1s.len() as u32,
+                num_g2_points,
+                start_from,
+            ] {
+                file.write_all(&field.to_be_bytes()).unwrap();
+            }
+            for point in g1s {
+                write_ec_point(&mut file, point);
+            }
+            if transcript_number == 0 {
+                write_ec_point(&mut file, &from_perpetual_powers_of_tau.s_g2);
+            }
+        }
+
+        let from_aztec_ignition = Srs::<Bn256>::read(
+            &mut File::open(aztec_ignition::transcript_path(&dir, 0)).unwrap(),
+            SrsFormat::AztecIgnition(dir),
+        );
+        assert_eq!(from_perpetual_powers_of_tau, from_aztec_ignition);
+    }
+
+    #[test]
+    fn test_perpetual_powers_of_tau_uncompressed() {
+        fn write_ec_point<C: CurveAffine>(writer: &mut impl Write, point: Option<&C>) {
+            match point.map(|point| point.coordinates().unwrap()) {
+                Some(coordinates) => {
+                    for fe in [coordinates.x(), coordinates.y()] {
+                        let mut repr = fe.to_repr();
+                        repr.as_mut().reverse();
+                        writer.write_all(repr.as_ref()).unwrap();
+                    }
+                }
+                None => {
+                    let mut bytes = vec![0; 2 * C::Base::default().to_repr().as_ref().len()];
+                    bytes[0] = 0b0100_0000;
+                    writer.write_all(&bytes).unwrap();
+                }
+            }
+        }
+
+        const PATH: &str = "./src/testdata/perpetual-powers-of-tau/bn254-8";
+        let from_perpetual_powers_of_tau = Srs::<Bn256>::read(
+            &mut File::open(PATH).unwrap(),
+            SrsFormat::PerpetualPowerOfTau(8),
+        );
+
+        // Write a challenge with `2^8` powers, where only tau_g1 and tau_g2
+        // are filled and others are identity.
+        let n = 1 << 8;
+        let mut buf = vec![0; 64];
+        for i in 0..2 * n - 1 {
+            write_ec_point(&mut buf, from_perpetual_powers_of_tau.g.get(i));
+        }
+        for i in 0..n {
+            let g2 = match i {
+                0 => Some(&from_perpetual_powers_of_tau.g2),
+                1 => Some(&from_perpetual_powers_of_tau.s_g2),
+                _ => None,
+            };
+            write_ec_point(&mut buf, g2);
+        }
+        for _ in 0..2 * n {
+            write_ec_point::<G1Affine>(&mut buf, None);
+        }
+        write_ec_point::<G2Affine>(&mut buf, None);
+
+        assert_eq!(
+            SrsFormat::detect::<Bn256, _>(&mut Cursor::new(&buf)).unwrap(),
+            Detection {
+                format: SrsFormat::PerpetualPowerOfTauUncompressed(8),
+                k: 8,
+                confidence: Confidence::Medium,
+            }
+        );
+        assert_eq!(
+            from_perpetual_powers_of_tau,
+            Srs::<Bn256>::read(
+                &mut Cursor::new(&buf),
+                SrsFormat::PerpetualPowerOfTauUncompressed(8),
+            )
+        );
+
+        let mut written = Vec::new();
+        from_perpetual_powers_of_tau
+            .write_as(&mut written, SrsFormat::PerpetualPowerOfTauUncompressed(8));
+        assert_eq!(written, buf);
+    }
+
+    #[test]
+    fn test_zcash_encoding() {
+        use halo2_curves::group::prime::PrimeCurveAffine;
+
+        fn round_trip<C: CurveAffine>(points: &[C]) {
+            assert!(!zcash::has_sign_flag::<C::Base>());
+            for compression in [Compression::Compressed, Compression::Uncompressed] {
+                let bytes = zcash::encode(&C::identity(), compression);
+                assert_eq!(bytes[0], zcash::INFINITY_FLAG);
+                assert!(bytes[1..].iter().all(|byte| *byte == 0));
+                assert_eq!(zcash::decode(&bytes, compression), Some(C::identity()));
+
+                // Bit of the sign flag of BLS12-381 is part of `x` on BN254.
+                let mut with_sign_bit = 0;
+                for point in points {
+                    let bytes = zcash::encode(point, compression);
+                    if bytes[0] & zcash::SIGN_FLAG != 0 {
+                        with_sign_bit += 1;
+                    }
+                    assert_eq!(bytes[0] & zcash::INFINITY_FLAG, 0);
+                    assert_eq!(zcash::decode(&bytes, compression), Some(*point));
+                }
+                assert!(with_sign_bit > 0);
+            }
+
+            // Uncompressed points don't have the compression flag.
+            let mut bytes = zcash::encode_uncompressed(&points[0]);
+            bytes[0] |= zcash::COMPRESSION_FLAG;
+            assert_eq!(zcash::decode_uncompressed::<C>(&bytes), None);
+        }
+
+        let points = (0..1000)
+            .map(|_| (G1Affine::generator() * Fr::random(OsRng)).to_affine())
+            .collect::<Vec<_>>();
+        round_trip(&points);
+        let points = (0..1000)
+            .map(|_| (G2Affine::generator() * Fr::random(OsRng)).to_affine())
+            .collect::<Vec<_>>();
+        round_trip(&points);
+    }
+
+    #[test]
+    fn test_perpetual_powers_of_tau_verify_chain() {
+        use perpetual_powers_of_tau::{
+            verify_chain, write_accumulator, write_public_key, PublicKey,
+        };
+
+        const K: u32 = 2;
+        let [tau, alpha, beta, x_tau, x_alpha, x_beta] = [(); 6].map(|_| Fr::random(OsRng));
+
+        let mut challenge = Vec::new();
+        write_accumulator(
+            &mut challenge,
+            K,
+            &[0; 64],
+            &insecure_accumulator(K, tau, alpha, beta),
+            Compression::Uncompressed,
+        )
+        .unwrap();
+        let digest = *blake2b_simd::blake2b(&challenge).as_array();
+
+        let (tau_g1, tau_g2) = key_pair(&digest, x_tau, 0);
+        let (alpha_g1, alpha_g2) = key_pair(&digest, x_alpha, 1);
+        let (beta_g1, beta_g2) = key_pair(&digest, x_beta, 2);
+        let mut public_key = PublicKey::<Bn256> {
+            tau_g1,
+            alpha_g1,
+            beta_g1,
+            tau_g2,
+            alpha_g2,
+            beta_g2,
+        };
+
+        let response = |public_key: &PublicKey<Bn256>| {
+            let mut response = Vec::new();
+            write_accumulator(
+                &mut response,
+                K,
+                &digest,
+                &insecure_accumulator(K, tau * x_tau, alpha * x_alpha, beta * x_beta),
+                Compression::Compressed,
+            )
+            .unwrap();
+            write_public_key(&mut response, public_key).unwrap();
+            response
+        };
+        verify_chain::<Bn256, _>(
+            &mut [(
+                Cursor::new(challenge.clone()),
+                Cursor::new(response(&public_key)),
+            )],
+            K,
+        )
+        .unwrap();
+
+        public_key.tau_g2 = (public_key.tau_g2 * x_tau).to_affine();
+        assert!(matches!(
+            verify_chain::<Bn256, _>(
+                &mut [(Cursor::new(challenge), Cursor::new(response(&public_key)))],
+                K,
+            ),
+            Err(SrsError::InvalidContribution {
+                index: 0,
+                check: "proof of knowledge"
+            })
+        ));
+    }
+
+    #[test]
+    fn test_snarkjs_verify_contributions() {
+        use perpetual_powers_of_tau::PublicKey;
+        use snarkjs::{
+            first_challenge_hash, verify_contributions, verify_contributions_from,
+            write_accumulator, Contribution, ContributionReport,
+        };
+
+        // Hash of the initial challenge of `ceremony_power` 28, which takes
+        // minutes to recompute.
+        let first_challenge: [u8; 64] = hex::decode(
+            "93da91920d5a54a8a0fde55cd9dc3a10c4f3eef768b62c0948741370864254b4\
+             c1920f3f29d4ebc0ef3acecf2e2db63a755713d77e1ed77347a56fbc317c7a93",
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+        let file = std::fs::read("./src/testdata/snarkjs/bn254-8").unwrap();
+        let header = snarkjs::read_header::<Bn256, _>(&mut Cursor::new(&file)).unwrap();
+        assert!(header.section(snarkjs::LAGRANGE_G1_SECTION_ID).is_some());
+        let reports =
+            verify_contributions_from::<Bn256, _>(&mut Cursor::new(&file), first_challenge)
+                .unwrap();
+        assert_eq!(reports.len(), 55);
+        let beacon = reports[54].beacon.clone().unwrap();
+
+        // Secrets of the beacon no longer match its key.
+        let mut tampered = file.clone();
+        let offset = tampered
+            .windows(beacon.hash.len())
+            .position(|window| window == beacon.hash)
+            .unwrap();
+        tampered[offset] ^= 1;
+        assert!(matches!(
+            verify_contributions_from::<Bn256, _>(&mut Cursor::new(tampered), first_challenge),
+            Err(SrsError::InvalidContribution {
+                index: 54,
+                check: "beacon (tau)"
+            })
+        ));
+
+        // Swap the last 2 points of sections in Lagrange basis, where the
+        // ones of tauG1 are in the extra `2^(power + 1)`.
+        for id in [
+            snarkjs::LAGRANGE_G1_SECTION_ID,
+            snarkjs::LAGRANGE_ALPHA_TAU_G1_SECTION_ID,
+        ] {
+            let mut tampered = file.clone();
+            let section = header.section(id).unwrap();
+            let end = (section.offset + section.size) as usize;
+            tampered[end - 128..end].rotate_left(64);
+            assert!(matches!(
+                verify_contributions_from::<Bn256, _>(&mut Cursor::new(tampered), first_challenge),
+                Err(SrsError::ValidationFailed("lagrange"))
+            ));
+        }
+
+        const K: u32 = 2;
+        let [tau, alpha, beta] = [(); 3].map(|_| Fr::random(OsRng));
+        let challenge = first_challenge_hash::<Bn256>(K);
+
+        let (tau_g1, tau_g2) = key_pair(&challenge, tau, 0);
+        let (alpha_g1, alpha_g2) = key_pair(&challenge, alpha, 1);
+        let (beta_g1, beta_g2) = key_pair(&challenge, beta, 2);
+
+        let accumulator = insecure_accumulator(K, tau, alpha, beta);
+        let mut contribution = Contribution::<Bn256> {
+            tau_g1: accumulator.tau_g1[1],
+            tau_g2: accumulator.tau_g2[1],
+            alpha_g1: accumulator.alpha_tau_g1[0],
+            beta_g1: accumulator.beta_tau_g1[0],
+            beta_g2: accumulator.beta_g2,
+            key: PublicKey {
+                tau_g1,
+                alpha_g1,
+                beta_g1,
+                tau_g2,
+                alpha_g2,
+                beta_g2,
+            },
+            partial_hash: Vec::new(),
+            next_challenge: [0; 64],
+            name: Some("alice".to_string()),
+            beacon: None,
+        };
+        let hash_chain = |key: &PublicKey<Bn256>| {
+            let mut response = blake2b_simd::State::new();
+            perpetual_powers_of_tau::write_accumulator(
+                &mut response,
+                K,
+                &challenge,
+                &accumulator,
+                Compression::Compressed,
+            )
+            .unwrap();
+            perpetual_powers_of_tau::write_public_key(&mut response, key).unwrap();
+            let mut next_challenge = blake2b_simd::State::new();
+            perpetual_powers_of_tau::write_accumulator(
+                &mut next_challenge,
+                K,
+                response.finalize().as_array(),
+                &accumulator,
+                Compression::Uncompressed,
+            )
+            .unwrap();
+            *next_challenge.finalize().as_array()
+        };
+        contribution.next_challenge = hash_chain(&contribution.key);
+
+        let mut buf = Vec::new();
+        write_accumulator(&mut buf, K, &accumulator, &[contribution.clone()]).unwrap();
+        assert_eq!(
+            verify_contributions::<Bn256, _>(&mut Cursor::new(buf)).unwrap(),
+            vec![ContributionReport {
+                index: 0,
+                name: Some("alice".to_string()),
+                beacon: None,
+                next_challenge: contribution.next_challenge,
+            }]
+        );
+
+        let mut wrong = contribution.clone();
+        wrong.next_challenge = [1; 64];
+        let mut buf = Vec::new();
+        write_accumulator(&mut buf, K, &accumulator, &[wrong]).unwrap();
+        assert!(matches!(
+            verify_contributions::<Bn256, _>(&mut Cursor::new(buf)),
+            Err(SrsError::ValidationFailed("hash chain"))
+        ));
+
+        contribution.key.alpha_g2 = contribution.key.beta_g2;
+        contribution.next_challenge = hash_chain(&contribution.key);
+        let mut buf = Vec::new();
+        write_accumulator(&mut buf, K, &accumulator, &[contribution]).unwrap();
+        assert!(matches!(
+            verify_contributions::<Bn256, _>(&mut Cursor::new(buf)),
+            Err(SrsError::InvalidContribution {
+                index: 0,
+                check: "key (alpha)"
+            })
+        ));
+
+        // Without contributions, the accumulator must be all generators.
+        let mut buf = Vec::new();
+        write_accumulator(&mut buf, K, &accumulator, &[]).unwrap();
+        assert!(matches!(
+            verify_contributions::<Bn256, _>(&mut Cursor::new(buf)),
+            Err(SrsError::ValidationFailed("final accumulator"))
+        ));
+        let initial = insecure_accumulator(K, Fr::one(), Fr::one(), Fr::one());
+        let mut buf = Vec::new();
+        write_accumulator(&mut buf, K, &initial, &[]).unwrap();
+        assert_eq!(
+            verify_contributions::<Bn256, _>(&mut Cursor::new(buf)).unwrap(),
+            vec![]
+        );
     }
 }
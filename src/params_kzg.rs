@@ -0,0 +1,35 @@
+//! Conversion between [`Srs`] and `ParamsKZG` of `halo2_proofs`, which goes
+//! through the `SerdeFormat::RawBytes` layout in memory since `ParamsKZG`
+//! doesn't expose its fields.
+
+use crate::{Srs, SrsFormat};
+use halo2_curves::{pairing::MultiMillerLoop, serde::SerdeObject};
+use halo2_proofs::{poly::kzg::commitment::ParamsKZG, SerdeFormat};
+use std::{fmt::Debug, io::Cursor};
+
+impl<M: MultiMillerLoop + Debug> From<Srs<M>> for ParamsKZG<M>
+where
+    M::G1Affine: SerdeObject,
+    M::G2Affine: SerdeObject,
+{
+    fn from(srs: Srs<M>) -> Self {
+        let mut buf = Vec::new();
+        srs.write_raw(&mut buf);
+        // Points of `Srs` are already on curve, so the check is skipped.
+        ParamsKZG::read_custom(&mut buf.as_slice(), SerdeFormat::RawBytesUnchecked).unwrap()
+    }
+}
+
+impl<M: MultiMillerLoop + Debug> From<ParamsKZG<M>> for Srs<M>
+where
+    M::G1Affine: SerdeObject,
+    M::G2Affine: SerdeObject,
+{
+    fn from(params: ParamsKZG<M>) -> Self {
+        let mut buf = Vec::new();
+        params
+            .write_custom(&mut buf, SerdeFormat::RawBytes)
+            .unwrap();
+        Srs::read(&mut Cursor::new(buf), SrsFormat::PseRaw)
+    }
+}
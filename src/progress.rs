@@ -0,0 +1,126 @@
+use crate::SrsError;
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Phase of a long running operation reported to [`ProgressSink`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// Reading `g` from the source, counted in points.
+    Read,
+    /// Computing `g_lagrange` from `g`, counted in points processed by each
+    /// pass of the out of core FFT, or each butterfly round of the in memory
+    /// one.
+    Lagrange,
+    /// Validating points are successive powers of tau, counted in ratios of
+    /// successive points combined.
+    Validate,
+    /// Writing outputs, counted in points of `g` written.
+    Write,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Phase::Read => "Reading",
+            Phase::Lagrange => "Computing Lagrange basis",
+            Phase::Validate => "Validating",
+            Phase::Write => "Writing",
+        })
+    }
+}
+
+/// Receiver of progress of long running operations, which can be forwarded
+/// to a progress bar or telemetry. All methods do nothing by default.
+pub trait ProgressSink: Send + Sync {
+    fn started(&self, _phase: Phase, _total: u64) {}
+
+    fn advanced(&self, _phase: Phase, _processed: u64, _total: u64) {}
+
+    fn finished(&self, _phase: Phase) {}
+}
+
+/// Token to cancel operations given a [`Progress`] with it from another
+/// thread, which is checked between chunks and fails them with
+/// [`SrsError::Cancelled`].
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Optional [`ProgressSink`] and [`CancellationToken`] passed to long running
+/// operations, where the default one reports nothing and never cancels.
+#[derive(Clone, Default)]
+pub struct Progress {
+    sink: Option<Arc<dyn ProgressSink>>,
+    cancellation: Option<CancellationToken>,
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Progress")
+            .field("sink", &self.sink.is_some())
+            .field("cancellation", &self.cancellation)
+            .finish()
+    }
+}
+
+impl Progress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_sink(mut self, sink: impl ProgressSink + 'static) -> Self {
+        self.sink = Some(Arc::new(sink));
+        self
+    }
+
+    pub fn with_cancellation(mut self, cancellation: CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    fn check_cancelled(&self) -> Result<(), SrsError> {
+        match &self.cancellation {
+            Some(cancellation) if cancellation.is_cancelled() => Err(SrsError::Cancelled),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn start(&self, phase: Phase, total: u64) -> Result<(), SrsError> {
+        self.check_cancelled()?;
+        if let Some(sink) = &self.sink {
+            sink.started(phase, total);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn advance(&self, phase: Phase, processed: u64, total: u64) -> Result<(), SrsError> {
+        if let Some(sink) = &self.sink {
+            sink.advanced(phase, processed, total);
+        }
+        self.check_cancelled()
+    }
+
+    pub(crate) fn finish(&self, phase: Phase) {
+        if let Some(sink) = &self.sink {
+            sink.finished(phase);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{
+    arithmetic::g_to_lagrange_out_of_core,
+    util::{ec_point_raw_size, ec_point_repr_size, pse},
+    Phase, Progress, SrsError, SrsFormat, SrsReader,
+};
+use halo2_curves::{pairing::MultiMillerLoop, serde::SerdeObject};
+use std::{cell::RefCell, io, marker::PhantomData, mem, ops::Range};
+
+/// Writer of SRS in [`SrsFormat::Pse`], [`SrsFormat::PseRaw`] or
+/// [`SrsFormat::PseRawUnchecked`] that never holds the whole SRS in memory.
+///
+/// It takes `g` incrementally and writes them directly, then on
+/// [`StreamingSrsWriter::finish`] appends `g_lagrange` computed out of core
+/// from the written `g`, and the G2 points. The `k` in header is determined
+/// by the number of points taken, so `writer` needs to be readable and
+/// seekable, e.g. a [`File`] opened for both reading and writing.
+///
+/// [`File`]: std::fs::File
+pub struct StreamingSrsWriter<M: MultiMillerLoop, W, S> {
+    writer: W,
+    scratch: S,
+    raw: bool,
+    memory_budget: usize,
+    n: usize,
+    progress: Progress,
+    _marker: PhantomData<M>,
+}
+
+impl<M: MultiMillerLoop, W, S> StreamingSrsWriter<M, W, S>
+where
+    M::G1Affine: SerdeObject,
+    M::G2Affine: SerdeObject,
+    W: io::Read + io::Write + io::Seek,
+    S: io::Read + io::Write + io::Seek,
+{
+    /// Returns a writer with `scratch` for computing `g_lagrange`, which
+    /// needs space of `2^(k+1)` points in raw format, and keeps about
+    /// `memory_budget` bytes of points in memory.
+    pub fn new(
+        mut writer: W,
+        scratch: S,
+        format: SrsFormat,
+        memory_budget: usize,
+    ) -> Result<Self, SrsError> {
+        let raw = match format {
+            SrsFormat::Pse => false,
+            SrsFormat::PseRaw | SrsFormat::PseRawUnchecked => true,
+            _ => return Err(SrsError::UnsupportedFormat("streaming writer")),
+        };
+        // Placeholder of `k`, which is filled in when finished.
+        writer.seek(io::SeekFrom::Start(0))?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        Ok(Self {
+            writer,
+            scratch,
+            raw,
+            memory_budget,
+            n: 0,
+            progress: Progress::default(),
+            _marker: PhantomData,
+        })
+    }
+
+    /// Reports to `progress` when copying and finishing, and checks its
+    /// cancellation between chunks.
+    pub fn with_progress(mut self, progress: Progress) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    fn g1_size(&self) -> usize {
+        if self.raw {
+            ec_point_raw_size::<M::G1Affine>()
+        } else {
+            ec_point_repr_size::<M::G1Affine>()
+        }
+    }
+
+    /// Returns number of points in `g` written so far.
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Appends points to `g`.
+    pub fn write_g1s(&mut self, g: &[M::G1Affine]) -> Result<(), SrsError> {
+        let mut bytes = Vec::with_capacity(g.len() * self.g1_size());
+        if self.raw {
+            pse::write_ec_points::<_, _, true>(&mut bytes, g)?;
+        } else {
+            pse::write_ec_points::<_, _, false>(&mut bytes, g)?;
+        }
+        self.writer.write_all(&bytes)?;
+        self.n += g.len();
+        Ok(())
+    }
+
+    /// Appends points of `g` in `range` read by `format`, in chunks within
+    /// the memory budget.
+    pub fn copy_g1s_from<R: io::Read + io::Seek>(
+        &mut self,
+        reader: &mut R,
+        format: &impl SrsReader<M>,
+        range: Range<usize>,
+    ) -> Result<(), SrsError> {
+        let chunk_size = (self.memory_budget / mem::size_of::<M::G1Affine>()).max(1);
+        let total = range.len() as u64;
+        self.progress.start(Phase::Read, total)?;
+        for start in range.clone().step_by(chunk_size) {
+            let end = (start + chunk_size).min(range.end);
+            let g = format.read_g1s(reader, start..end)?;
+            self.write_g1s(&g)?;
+            self.progress
+                .advance(Phase::Read, (end - range.start) as u64, total)?;
+        }
+        self.progress.finish(Phase::Read);
+        Ok(())
+    }
+
+    /// Fills in `k`, appends `g_lagrange` and `g2s`, and returns the
+    /// underlying writer. The number of points written needs to be a power
+    /// of 2.
+    pub fn finish(mut self, g2s: [M::G2Affine; 2]) -> Result<W, SrsError> {
+        if !self.n.is_power_of_two() {
+            return Err(SrsError::ValidationFailed("number of g is a power of 2"));
+        }
+        let k = self.n.trailing_zeros();
+        self.writer.seek(io::SeekFrom::Start(0))?;
+        self.writer.write_all(&k.to_le_bytes())?;
+
+        // Reads back `g` written, while `g_lagrange` is appended after them.
+        let g1_size = self.g1_size();
+        let raw = self.raw;
+        let writer = RefCell::new(&mut self.writer);
+        let read_g = |range: Range<usize>| {
+            let mut writer = writer.borrow_mut();
+            let offset = pse::G1_OFFSET + (range.start * g1_size) as u64;
+            writer.seek(io::SeekFrom::Start(offset))?;
+            if raw {
+                pse::read_ec_points::<_, _, true, false>(&mut *writer, range.len(), "g1")
+            } else {
+                pse::read_ec_points::<_, _, false, true>(&mut *writer, range.len(), "g1")
+            }
+        };
+        let mut offset = pse::G1_OFFSET + (self.n * g1_size) as u64;
+        let write_g_lagrange = |g_lagrange: &[M::G1Affine]| {
+            let mut bytes = Vec::with_capacity(g_lagrange.len() * g1_size);
+            if raw {
+                pse::write_ec_points::<_, _, true>(&mut bytes, g_lagrange)?;
+            } else {
+                pse::write_ec_points::<_, _, false>(&mut bytes, g_lagrange)?;
+            }
+            let mut writer = writer.borrow_mut();
+            writer.seek(io::SeekFrom::Start(offset))?;
+            writer.write_all(&bytes)?;
+            offset += bytes.len() as u64;
+            Ok(())
+        };
+        g_to_lagrange_out_of_core(
+            read_g,
+            k,
+            &mut self.scratch,
+            self.memory_budget,
+            write_g_lagrange,
+            &self.progress,
+        )?;
+
+        let offset = pse::G1_OFFSET + (2 * self.n * g1_size) as u64;
+        self.writer.seek(io::SeekFrom::Start(offset))?;
+        if self.raw {
+            pse::write_ec_points::<_, _, true>(&mut self.writer, &g2s)?;
+        } else {
+            pse::write_ec_points::<_, _, false>(&mut self.writer, &g2s)?;
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
@@ -1,5 +1,7 @@
+use crate::SrsError;
 use halo2_curves::{group::ff::PrimeField, serde::SerdeObject, CurveAffine, FieldExt};
 use num_bigint::BigUint;
+use rayon::prelude::*;
 
 pub fn field_repr_size<F: PrimeField>() -> usize {
     F::Repr::default().as_ref().len()
@@ -15,7 +17,7 @@ pub fn ec_point_raw_size<C: CurveAffine + SerdeObject>() -> usize {
     buf.len()
 }
 
-fn modulus<F: FieldExt>() -> BigUint {
+pub(crate) fn modulus<F: FieldExt>() -> BigUint {
     BigUint::from_bytes_le((-F::one()).to_repr().as_ref()) + 1u64
 }
 
@@ -26,27 +28,70 @@ fn mont_r<F: FieldExt>() -> F {
     F::from_repr(repr).unwrap()
 }
 
+/// Decodes encoded points in parallel, and returns the error of any point
+/// failed to decode.
+fn decode_ec_points<T: Sync, C: Send>(
+    encodings: &[T],
+    decode: impl Fn(usize, &T) -> Result<C, SrsError> + Sync,
+) -> Result<Vec<C>, SrsError> {
+    encodings
+        .par_iter()
+        .enumerate()
+        .map(|(index, encoding)| decode(index, encoding))
+        .collect()
+}
+
+/// Whether points are encoded with both coordinates, or only `x` with flags.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Compression {
+    Compressed,
+    Uncompressed,
+}
+
 pub mod pse {
     use crate::{
-        arithmetic::parallelize,
-        util::{ec_point_raw_size, ec_point_repr_size},
+        arithmetic::g_to_lagrange_out_of_core,
+        util::{decode_ec_points, ec_point_raw_size, ec_point_repr_size},
+        Phase, Progress, SrsError, SrsReader, SrsWriter,
     };
     use byteorder::{LittleEndian, ReadBytesExt};
     use halo2_curves::{pairing::MultiMillerLoop, serde::SerdeObject, CurveAffine};
-    use std::io;
+    use std::{io, mem, ops::Range};
 
     pub const G1_OFFSET: u64 = 4;
 
-    pub fn read_k<R: io::Read + io::Seek>(reader: &mut R) -> u32 {
-        reader.seek(io::SeekFrom::Start(0)).unwrap();
-        reader.read_u32::<LittleEndian>().unwrap()
+    pub fn read_k<R: io::Read + io::Seek>(reader: &mut R) -> Result<u32, SrsError> {
+        reader.seek(io::SeekFrom::Start(0))?;
+        Ok(reader.read_u32::<LittleEndian>()?)
     }
 
-    fn seek_g1_offset<R: io::Read + io::Seek>(reader: &mut R) {
-        reader.seek(io::SeekFrom::Start(G1_OFFSET)).unwrap();
+    fn seek_g1_offset<R: io::Read + io::Seek>(reader: &mut R) -> Result<(), SrsError> {
+        reader.seek(io::SeekFrom::Start(G1_OFFSET))?;
+        Ok(())
+    }
+
+    pub fn file_size<M: MultiMillerLoop, const RAW: bool>(k: u32) -> u64
+    where
+        M::G1Affine: SerdeObject,
+        M::G2Affine: SerdeObject,
+    {
+        let (g1_size, g2_size) = if RAW {
+            (
+                ec_point_raw_size::<M::G1Affine>(),
+                ec_point_raw_size::<M::G2Affine>(),
+            )
+        } else {
+            (
+                ec_point_repr_size::<M::G1Affine>(),
+                ec_point_repr_size::<M::G2Affine>(),
+            )
+        };
+        G1_OFFSET + ((2 * g1_size as u64) << k) + 2 * g2_size as u64
     }
 
-    fn seek_g2_offset<M: MultiMillerLoop, R: io::Read + io::Seek, const RAW: bool>(reader: &mut R)
+    fn seek_g2_offset<M: MultiMillerLoop, R: io::Read + io::Seek, const RAW: bool>(
+        reader: &mut R,
+    ) -> Result<(), SrsError>
     where
         M::G1Affine: SerdeObject,
     {
@@ -55,33 +100,131 @@ pub mod pse {
         } else {
             ec_point_repr_size::<M::G1Affine>()
         };
-        let offset = 4 + g1_size * 2 * (1 << read_k(reader));
-        reader.seek(io::SeekFrom::Start(offset as u64)).unwrap();
+        let offset = 4 + g1_size * 2 * (1 << read_k(reader)?);
+        reader.seek(io::SeekFrom::Start(offset as u64))?;
+        Ok(())
     }
 
-    fn read_ec_points<C: CurveAffine + SerdeObject, R: io::Read, const RAW: bool>(
+    /// Reads `n` points, which skips the on curve check in raw format if not
+    /// `CHECKED`.
+    pub(crate) fn read_ec_points<
+        C: CurveAffine + SerdeObject,
+        R: io::Read,
+        const RAW: bool,
+        const CHECKED: bool,
+    >(
         reader: &mut R,
         n: usize,
-    ) -> Vec<C> {
+        section: &'static str,
+    ) -> Result<Vec<C>, SrsError> {
         if RAW {
-            (0..n)
-                .map(|_| <C as SerdeObject>::read_raw(reader))
-                .collect::<Result<Vec<_>, _>>()
-                .unwrap()
+            let size = ec_point_raw_size::<C>();
+            let mut bytes = vec![0; n * size];
+            reader.read_exact(&mut bytes)?;
+            decode_raw_ec_points::<C, CHECKED>(&bytes, section)
         } else {
             let mut reprs = vec![C::Repr::default(); n];
             for repr in reprs.iter_mut() {
-                reader.read_exact(repr.as_mut()).unwrap();
+                reader.read_exact(repr.as_mut())?;
             }
 
-            let mut points = vec![C::default(); n];
-            parallelize(&mut points, |points, start| {
-                for (i, point) in points.iter_mut().enumerate() {
-                    *point = C::from_bytes(&reprs[start + i]).unwrap();
-                }
-            });
-            points
+            decode_ec_points(&reprs, |index, repr| {
+                Option::from(C::from_bytes(repr))
+                    .ok_or(SrsError::InvalidPointEncoding { index, section })
+            })
+        }
+    }
+
+    /// Decodes consecutive points in raw format in parallel, which skips the
+    /// on curve check if not `CHECKED`.
+    pub(crate) fn decode_raw_ec_points<C: CurveAffine + SerdeObject, const CHECKED: bool>(
+        bytes: &[u8],
+        section: &'static str,
+    ) -> Result<Vec<C>, SrsError> {
+        let raws = bytes.chunks(ec_point_raw_size::<C>()).collect::<Vec<_>>();
+        decode_ec_points(&raws, |index, raw| {
+            if CHECKED {
+                C::from_raw_bytes(raw).ok_or(SrsError::NotOnCurve { index, section })
+            } else {
+                Ok(C::from_raw_bytes_unchecked(raw))
+            }
+        })
+    }
+
+    pub(crate) fn write_ec_points<C: CurveAffine + SerdeObject, W: io::Write, const RAW: bool>(
+        writer: &mut W,
+        points: &[C],
+    ) -> Result<(), SrsError> {
+        for point in points {
+            if RAW {
+                point.write_raw(writer)?;
+            } else {
+                writer.write_all(point.to_bytes().as_ref())?;
+            }
         }
+        Ok(())
+    }
+
+    pub fn write<M: MultiMillerLoop, W: io::Write, const RAW: bool>(
+        writer: &mut W,
+        k: u32,
+        g1s: &[M::G1Affine],
+        g1s_lagrange: &[M::G1Affine],
+        g2s: &[M::G2Affine],
+    ) -> Result<(), SrsError>
+    where
+        M::G1Affine: SerdeObject,
+        M::G2Affine: SerdeObject,
+    {
+        writer.write_all(&k.to_le_bytes())?;
+        write_ec_points::<_, _, RAW>(writer, g1s)?;
+        write_ec_points::<_, _, RAW>(writer, g1s_lagrange)?;
+        write_ec_points::<_, _, RAW>(writer, g2s)?;
+        Ok(())
+    }
+
+    /// Writes in the same layout as [`write`] with `g` read by range with
+    /// `read_g`, where `g_lagrange` is computed out of core over `scratch`
+    /// with about `memory_budget` bytes in memory, so neither section is ever
+    /// fully in memory. Chunks of `g` are reported to `progress` as
+    /// [`Phase::Write`] in points.
+    pub fn write_out_of_core<M, W, S, const RAW: bool>(
+        writer: &mut W,
+        k: u32,
+        mut read_g: impl FnMut(Range<usize>) -> Result<Vec<M::G1Affine>, SrsError>,
+        g2s: &[M::G2Affine],
+        scratch: &mut S,
+        memory_budget: usize,
+        progress: &Progress,
+    ) -> Result<(), SrsError>
+    where
+        M: MultiMillerLoop,
+        M::G1Affine: SerdeObject,
+        M::G2Affine: SerdeObject,
+        W: io::Write,
+        S: io::Read + io::Write + io::Seek,
+    {
+        let n = 1 << k;
+        let chunk_size = (memory_budget / mem::size_of::<M::G1Affine>()).clamp(1, n);
+
+        writer.write_all(&k.to_le_bytes())?;
+        progress.start(Phase::Write, n as u64)?;
+        for start in (0..n).step_by(chunk_size) {
+            let end = (start + chunk_size).min(n);
+            write_ec_points::<_, _, RAW>(writer, &read_g(start..end)?)?;
+            progress.advance(Phase::Write, end as u64, n as u64)?;
+        }
+        progress.finish(Phase::Write);
+        g_to_lagrange_out_of_core(
+            &mut read_g,
+            k,
+            scratch,
+            memory_budget,
+            |g_lagrange| write_ec_points::<_, _, RAW>(writer, g_lagrange),
+            progress,
+        )?;
+        write_ec_points::<_, _, RAW>(writer, g2s)?;
+        Ok(())
     }
 
     pub fn read_g1s<
@@ -92,14 +235,14 @@ pub mod pse {
     >(
         reader: &mut R,
         n: usize,
-    ) -> Vec<M::G1Affine>
+    ) -> Result<Vec<M::G1Affine>, SrsError>
     where
         M::G1Affine: SerdeObject,
     {
         if !IN_PLACE {
-            seek_g1_offset(reader);
+            seek_g1_offset(reader)?;
         }
-        read_ec_points::<_, _, RAW>(reader, n)
+        read_ec_points::<_, _, RAW, true>(reader, n, "g1")
     }
 
     pub fn read_g2s<
@@ -110,211 +253,2185 @@ pub mod pse {
     >(
         reader: &mut R,
         n: usize,
-    ) -> Vec<M::G2Affine>
+    ) -> Result<Vec<M::G2Affine>, SrsError>
     where
         M::G1Affine: SerdeObject,
         M::G2Affine: SerdeObject,
     {
         if !IN_PLACE {
-            seek_g2_offset::<M, _, RAW>(reader);
+            seek_g2_offset::<M, _, RAW>(reader)?;
+        }
+        read_ec_points::<_, _, RAW, true>(reader, n, "g2")
+    }
+
+    /// Format of `pse/halo2`, in raw format if `RAW`, which corresponds to
+    /// `SerdeFormat::RawBytes`, otherwise `SerdeFormat::Processed`. Raw points
+    /// are read without the on curve check if not `CHECKED`, which
+    /// corresponds to `SerdeFormat::RawBytesUnchecked`.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct Pse<const RAW: bool, const CHECKED: bool = true>;
+
+    impl<const RAW: bool, const CHECKED: bool> Pse<RAW, CHECKED> {
+        fn g1_size<M: MultiMillerLoop>() -> usize
+        where
+            M::G1Affine: SerdeObject,
+        {
+            if RAW {
+                ec_point_raw_size::<M::G1Affine>()
+            } else {
+                ec_point_repr_size::<M::G1Affine>()
+            }
+        }
+    }
+
+    impl<M: MultiMillerLoop, const RAW: bool, const CHECKED: bool> SrsReader<M> for Pse<RAW, CHECKED>
+    where
+        M::G1Affine: SerdeObject,
+        M::G2Affine: SerdeObject,
+    {
+        fn read_k<R: io::Read + io::Seek>(&self, reader: &mut R) -> Result<u32, SrsError> {
+            read_k(reader)
+        }
+
+        fn read_g1s<R: io::Read + io::Seek>(
+            &self,
+            reader: &mut R,
+            range: Range<usize>,
+        ) -> Result<Vec<M::G1Affine>, SrsError> {
+            let offset = G1_OFFSET + (range.start * Self::g1_size::<M>()) as u64;
+            reader.seek(io::SeekFrom::Start(offset))?;
+            read_ec_points::<_, _, RAW, CHECKED>(reader, range.len(), "g1")
+        }
+
+        /// Reads `g_lagrange` only if `k` is the one of the file.
+        fn read_g1s_lagrange<R: io::Read + io::Seek>(
+            &self,
+            reader: &mut R,
+            k: u32,
+        ) -> Result<Option<Vec<M::G1Affine>>, SrsError> {
+            if read_k(reader)? != k {
+                return Ok(None);
+            }
+            let n = 1 << k;
+            let offset = G1_OFFSET + (n * Self::g1_size::<M>()) as u64;
+            reader.seek(io::SeekFrom::Start(offset))?;
+            read_ec_points::<_, _, RAW, CHECKED>(reader, n, "g1").map(Some)
+        }
+
+        fn read_g2s<R: io::Read + io::Seek>(
+            &self,
+            reader: &mut R,
+        ) -> Result<[M::G2Affine; 2], SrsError> {
+            seek_g2_offset::<M, _, RAW>(reader)?;
+            Ok(read_ec_points::<_, _, RAW, CHECKED>(reader, 2, "g2")?
+                .try_into()
+                .unwrap())
+        }
+    }
+
+    impl<M: MultiMillerLoop, const RAW: bool, const CHECKED: bool> SrsWriter<M> for Pse<RAW, CHECKED>
+    where
+        M::G1Affine: SerdeObject,
+        M::G2Affine: SerdeObject,
+    {
+        fn write<W: io::Write>(
+            &self,
+            writer: &mut W,
+            k: u32,
+            g: &[M::G1Affine],
+            g_lagrange: &[M::G1Affine],
+            g2s: [M::G2Affine; 2],
+        ) -> Result<(), SrsError> {
+            write::<M, _, RAW>(writer, k, g, g_lagrange, &g2s)
         }
-        read_ec_points::<_, _, RAW>(reader, n)
     }
 }
 
 pub mod perpetual_powers_of_tau {
-    use crate::{arithmetic::parallelize, util::ec_point_repr_size};
-    use halo2_curves::{pairing::MultiMillerLoop, CurveAffine};
-    use std::io;
+    use crate::{
+        arithmetic::{best_multiexp, same_ratio},
+        check_desired_k,
+        util::{
+            decode_ec_points, ec_point_repr_size, field_repr_size, modulus, mont_r, zcash,
+            Compression,
+        },
+        SrsError, SrsReader, SrsWriter,
+    };
+    use halo2_curves::{
+        group::{
+            ff::{Field, PrimeField},
+            prime::PrimeCurveAffine,
+            Curve, Group,
+        },
+        pairing::{Engine, MillerLoopResult, MultiMillerLoop},
+        CurveAffine, FieldExt,
+    };
+    use rand_core::OsRng;
+    use std::{io, iter, ops::Range};
 
     pub const G1_OFFSET: u64 = 64;
 
-    fn seek_g1_offset<R: io::Read + io::Seek>(reader: &mut R) {
-        reader.seek(io::SeekFrom::Start(G1_OFFSET)).unwrap();
+    fn seek_g1_offset<R: io::Read + io::Seek>(reader: &mut R) -> Result<(), SrsError> {
+        reader.seek(io::SeekFrom::Start(G1_OFFSET))?;
+        Ok(())
+    }
+
+    /// Returns size of a point, where compressed ones only contain `x` with
+    /// flags, and uncompressed ones contain `x` followed by `y`.
+    pub fn ec_point_size<C: CurveAffine>(compression: Compression) -> usize {
+        match compression {
+            Compression::Compressed => ec_point_repr_size::<C>(),
+            Compression::Uncompressed => 2 * field_repr_size::<C::Base>(),
+        }
     }
 
-    pub fn g2_offset<M: MultiMillerLoop>(k: u32) -> u64 {
-        let g1_size = ec_point_repr_size::<M::G1Affine>() as u64;
+    /// Returns size of a file for `2^k` powers, where challenges are written
+    /// uncompressed, and responses are written compressed with the public key
+    /// of the contributor appended.
+    ///
+    /// The file contains `2^(k+1) - 1` points of tau_g1, `2^k` points of
+    /// tau_g2, alpha_tau_g1 and beta_tau_g1, and one point of beta_g2.
+    pub fn file_size<M: MultiMillerLoop>(
+        k: u32,
+        compression: Compression,
+        with_public_key: bool,
+    ) -> u64 {
+        let g1_size = ec_point_size::<M::G1Affine>(compression);
+        let g2_size = ec_point_size::<M::G2Affine>(compression);
+        let n = 1 << k;
+        let public_key_size = if with_public_key {
+            6 * ec_point_size::<M::G1Affine>(Compression::Uncompressed)
+                + 3 * ec_point_size::<M::G2Affine>(Compression::Uncompressed)
+        } else {
+            0
+        };
+        G1_OFFSET + (((4 * n - 1) * g1_size + (n + 1) * g2_size + public_key_size) as u64)
+    }
+
+    pub fn g2_offset<M: MultiMillerLoop>(k: u32, compression: Compression) -> u64 {
+        let g1_size = ec_point_size::<M::G1Affine>(compression) as u64;
         G1_OFFSET + g1_size * (2 * (1 << k) - 1)
     }
 
-    fn seek_g2_offset<M: MultiMillerLoop, R: io::Read + io::Seek>(reader: &mut R, k: u32) {
-        let offset = g2_offset::<M>(k);
-        reader.seek(io::SeekFrom::Start(offset)).unwrap();
+    fn seek_g2_offset<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        reader: &mut R,
+        k: u32,
+        compression: Compression,
+    ) -> Result<(), SrsError> {
+        let offset = g2_offset::<M>(k, compression);
+        reader.seek(io::SeekFrom::Start(offset))?;
+        Ok(())
     }
 
-    fn read_ec_points<C: CurveAffine, R: io::Read + io::Seek>(reader: &mut R, n: usize) -> Vec<C> {
-        let mut reprs = vec![C::Repr::default(); n];
-        for repr in reprs.iter_mut() {
-            reader.read_exact(repr.as_mut()).unwrap();
-            repr.as_mut().reverse();
-        }
-
-        let mut points = vec![C::default(); n];
-        parallelize(&mut points, |points, start| {
-            for (i, point) in points.iter_mut().enumerate() {
-                let candidate = C::from_bytes(&reprs[start + i]).unwrap();
-                let minus_candidate = -candidate;
-
-                *point = if (candidate.coordinates().unwrap().y()
-                    < minus_candidate.coordinates().unwrap().y())
-                    ^ ((reprs[start + i].as_ref().last().unwrap() & 0b1000_0000) != 0)
-                {
-                    candidate
-                } else {
-                    minus_candidate
+    /// Reads `n` points in [`zcash`] encoding, where only the 2 most
+    /// significant bits are flags on BN254.
+    fn read_ec_points<C: CurveAffine, R: io::Read + io::Seek>(
+        reader: &mut R,
+        n: usize,
+        compression: Compression,
+        section: &'static str,
+    ) -> Result<Vec<C>, SrsError> {
+        let size = ec_point_size::<C>(compression);
+        let mut bytes = vec![0; n * size];
+        reader.read_exact(&mut bytes)?;
+        decode_ec_points(&bytes.chunks(size).collect::<Vec<_>>(), |index, bytes| {
+            zcash::decode(bytes, compression)
+                .ok_or(SrsError::InvalidPointEncoding { index, section })
+        })
+    }
+
+    /// Writes `n` points in [`zcash`] encoding, where compressed ones have
+    /// the most significant bit set if `y` is the greatest root, and both
+    /// have the second most significant bit set for identity. Points beyond
+    /// `points` are written as identity.
+    fn write_ec_points<C: CurveAffine, W: io::Write>(
+        writer: &mut W,
+        points: &[C],
+        n: usize,
+        compression: Compression,
+    ) -> Result<(), SrsError> {
+        for index in 0..n {
+            let point = points.get(index).copied().unwrap_or_else(C::identity);
+            writer.write_all(&zcash::encode(&point, compression))?;
+        }
+        Ok(())
+    }
+
+    /// Writes a file for `2^k` powers with `g1s` as tau_g1 and `g2s` as
+    /// tau_g2, with missing powers, alpha and beta sections written as
+    /// identity, and the hash of previous challenge filled with zeros, which is
+    /// a response if compressed, or a challenge if uncompressed.
+    pub fn write<M: MultiMillerLoop, W: io::Write>(
+        writer: &mut W,
+        k: u32,
+        g1s: &[M::G1Affine],
+        g2s: &[M::G2Affine],
+        compression: Compression,
+    ) -> Result<(), SrsError> {
+        let accumulator = Accumulator::<M> {
+            tau_g1: g1s.to_vec(),
+            tau_g2: g2s.to_vec(),
+            alpha_tau_g1: Vec::new(),
+            beta_tau_g1: Vec::new(),
+            beta_g2: M::G2Affine::identity(),
+        };
+        write_accumulator(
+            writer,
+            k,
+            &[0; G1_OFFSET as usize],
+            &accumulator,
+            compression,
+        )
+    }
+
+    /// All sections of a challenge or response, where each one might have
+    /// less points than the file when read partially.
+    #[derive(Clone, Debug)]
+    pub struct Accumulator<M: MultiMillerLoop> {
+        pub tau_g1: Vec<M::G1Affine>,
+        pub tau_g2: Vec<M::G2Affine>,
+        pub alpha_tau_g1: Vec<M::G1Affine>,
+        pub beta_tau_g1: Vec<M::G1Affine>,
+        pub beta_g2: M::G2Affine,
+    }
+
+    impl<M: MultiMillerLoop> PartialEq for Accumulator<M> {
+        fn eq(&self, other: &Self) -> bool {
+            self.tau_g1 == other.tau_g1
+                && self.tau_g2 == other.tau_g2
+                && self.alpha_tau_g1 == other.alpha_tau_g1
+                && self.beta_tau_g1 == other.beta_tau_g1
+                && self.beta_g2 == other.beta_g2
+        }
+    }
+
+    /// Reads hash of the previous file and all sections of a file for `2^k`
+    /// powers.
+    pub fn read_accumulator<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        reader: &mut R,
+        k: u32,
+        compression: Compression,
+    ) -> Result<([u8; G1_OFFSET as usize], Accumulator<M>), SrsError> {
+        let n = 1 << k;
+        let mut hash = [0; G1_OFFSET as usize];
+        reader.rewind()?;
+        reader.read_exact(&mut hash)?;
+        let accumulator = Accumulator {
+            tau_g1: read_ec_points(reader, 2 * n - 1, compression, "tau_g1")?,
+            tau_g2: read_ec_points(reader, n, compression, "tau_g2")?,
+            alpha_tau_g1: read_ec_points(reader, n, compression, "alpha_tau_g1")?,
+            beta_tau_g1: read_ec_points(reader, n, compression, "beta_tau_g1")?,
+            beta_g2: read_ec_points(reader, 1, compression, "beta_g2")?[0],
+        };
+        Ok((hash, accumulator))
+    }
+
+    /// Reads all sections of a file for `2^k` powers truncated to
+    /// `2^desired_k` powers, except tau_g1 truncated to `2^(desired_k+1) - 1`
+    /// powers, and tau_g2 has at least 2 powers. Files with `k = 0` are
+    /// rejected, as they have no tau_g2 beyond the generator.
+    pub fn read_sections<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        reader: &mut R,
+        k: u32,
+        compression: Compression,
+        desired_k: u32,
+    ) -> Result<Accumulator<M>, SrsError> {
+        if k == 0 {
+            return Err(SrsError::UnsupportedFormat(
+                "Perpetual Powers of Tau with k = 0",
+            ));
+        }
+        check_desired_k(desired_k, k)?;
+        let (n, desired_n) = (1 << k, 1 << desired_k);
+        let g1_size = ec_point_size::<M::G1Affine>(compression) as u64;
+        let g2_size = ec_point_size::<M::G2Affine>(compression) as u64;
+        let tau_g2_offset = g2_offset::<M>(k, compression);
+        let alpha_tau_g1_offset = tau_g2_offset + n * g2_size;
+        let beta_tau_g1_offset = alpha_tau_g1_offset + n * g1_size;
+        let beta_g2_offset = beta_tau_g1_offset + n * g1_size;
+
+        let mut read_g1s = |offset, n, section| {
+            reader.seek(io::SeekFrom::Start(offset))?;
+            read_ec_points::<M::G1Affine, _>(reader, n, compression, section)
+        };
+        let tau_g1 = read_g1s(G1_OFFSET, 2 * desired_n - 1, "tau_g1")?;
+        let alpha_tau_g1 = read_g1s(alpha_tau_g1_offset, desired_n, "alpha_tau_g1")?;
+        let beta_tau_g1 = read_g1s(beta_tau_g1_offset, desired_n, "beta_tau_g1")?;
+        let mut read_g2s = |offset, n, section| {
+            reader.seek(io::SeekFrom::Start(offset))?;
+            read_ec_points::<M::G2Affine, _>(reader, n, compression, section)
+        };
+        let tau_g2 = read_g2s(tau_g2_offset, desired_n.max(2), "tau_g2")?;
+        let beta_g2 = read_g2s(beta_g2_offset, 1, "beta_g2")?[0];
+
+        Ok(Accumulator {
+            tau_g1,
+            tau_g2,
+            alpha_tau_g1,
+            beta_tau_g1,
+            beta_g2,
+        })
+    }
+
+    /// Writes `hash` of the previous file and all sections for `2^k` powers,
+    /// with missing points written as identity.
+    pub fn write_accumulator<M: MultiMillerLoop, W: io::Write>(
+        writer: &mut W,
+        k: u32,
+        hash: &[u8; G1_OFFSET as usize],
+        accumulator: &Accumulator<M>,
+        compression: Compression,
+    ) -> Result<(), SrsError> {
+        let n = 1 << k;
+        writer.write_all(hash)?;
+        write_ec_points(writer, &accumulator.tau_g1, 2 * n - 1, compression)?;
+        write_ec_points(writer, &accumulator.tau_g2, n, compression)?;
+        write_ec_points(writer, &accumulator.alpha_tau_g1, n, compression)?;
+        write_ec_points(writer, &accumulator.beta_tau_g1, n, compression)?;
+        write_ec_points(writer, &[accumulator.beta_g2], 1, compression)?;
+        Ok(())
+    }
+
+    /// Public key of a contributor appended to the response, where each pair
+    /// of G1 points is `(s, s * x)` for a random `s` and the secret `x`, and
+    /// each G2 point is `s_g2 * x` with `s_g2` derived by [`compute_g2_s`].
+    #[derive(Clone, Debug)]
+    pub struct PublicKey<M: MultiMillerLoop> {
+        pub tau_g1: (M::G1Affine, M::G1Affine),
+        pub alpha_g1: (M::G1Affine, M::G1Affine),
+        pub beta_g1: (M::G1Affine, M::G1Affine),
+        pub tau_g2: M::G2Affine,
+        pub alpha_g2: M::G2Affine,
+        pub beta_g2: M::G2Affine,
+    }
+
+    /// Reads the public key at the end of a response for `2^k` powers.
+    pub fn read_public_key<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        reader: &mut R,
+        k: u32,
+    ) -> Result<PublicKey<M>, SrsError> {
+        let offset = file_size::<M>(k, Compression::Compressed, false);
+        reader.seek(io::SeekFrom::Start(offset))?;
+        let g1s = read_ec_points::<M::G1Affine, _>(
+            reader,
+            6,
+            Compression::Uncompressed,
+            "public_key_g1",
+        )?;
+        let g2s = read_ec_points::<M::G2Affine, _>(
+            reader,
+            3,
+            Compression::Uncompressed,
+            "public_key_g2",
+        )?;
+        Ok(PublicKey {
+            tau_g1: (g1s[0], g1s[1]),
+            alpha_g1: (g1s[2], g1s[3]),
+            beta_g1: (g1s[4], g1s[5]),
+            tau_g2: g2s[0],
+            alpha_g2: g2s[1],
+            beta_g2: g2s[2],
+        })
+    }
+
+    pub fn write_public_key<M: MultiMillerLoop, W: io::Write>(
+        writer: &mut W,
+        public_key: &PublicKey<M>,
+    ) -> Result<(), SrsError> {
+        let g1s = [public_key.tau_g1, public_key.alpha_g1, public_key.beta_g1]
+            .into_iter()
+            .flat_map(|(s, s_x)| [s, s_x])
+            .collect::<Vec<_>>();
+        let g2s = [public_key.tau_g2, public_key.alpha_g2, public_key.beta_g2];
+        write_ec_points(writer, &g1s, g1s.len(), Compression::Uncompressed)?;
+        write_ec_points(writer, &g2s, g2s.len(), Compression::Uncompressed)?;
+        Ok(())
+    }
+
+    /// ChaCha20 with 128-bit block counter and zero nonce, which outputs the
+    /// same stream as `ChaChaRng` of `rand` 0.4 used by the ceremony, and
+    /// the one of snarkjs used for beacons.
+    pub(crate) struct ChaChaRng {
+        state: [u32; 16],
+        buffer: [u32; 16],
+        index: usize,
+    }
+
+    impl ChaChaRng {
+        pub(crate) fn from_seed(key: [u32; 8]) -> Self {
+            let mut state = [0; 16];
+            state[..4].copy_from_slice(&[0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574]);
+            state[4..12].copy_from_slice(&key);
+            Self {
+                state,
+                buffer: [0; 16],
+                index: 16,
+            }
+        }
+
+        fn refill(&mut self) {
+            fn quarter_round(x: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+                x[a] = x[a].wrapping_add(x[b]);
+                x[d] = (x[d] ^ x[a]).rotate_left(16);
+                x[c] = x[c].wrapping_add(x[d]);
+                x[b] = (x[b] ^ x[c]).rotate_left(12);
+                x[a] = x[a].wrapping_add(x[b]);
+                x[d] = (x[d] ^ x[a]).rotate_left(8);
+                x[c] = x[c].wrapping_add(x[d]);
+                x[b] = (x[b] ^ x[c]).rotate_left(7);
+            }
+
+            let mut x = self.state;
+            for _ in 0..10 {
+                quarter_round(&mut x, 0, 4, 8, 12);
+                quarter_round(&mut x, 1, 5, 9, 13);
+                quarter_round(&mut x, 2, 6, 10, 14);
+                quarter_round(&mut x, 3, 7, 11, 15);
+                quarter_round(&mut x, 0, 5, 10, 15);
+                quarter_round(&mut x, 1, 6, 11, 12);
+                quarter_round(&mut x, 2, 7, 8, 13);
+                quarter_round(&mut x, 3, 4, 9, 14);
+            }
+            for (buffer, (x, state)) in self.buffer.iter_mut().zip(x.iter().zip(self.state)) {
+                *buffer = x.wrapping_add(state);
+            }
+            for counter in self.state[12..].iter_mut() {
+                *counter = counter.wrapping_add(1);
+                if *counter != 0 {
+                    break;
                 }
             }
-        });
-        points
+            self.index = 0;
+        }
+
+        fn next_u32(&mut self) -> u32 {
+            if self.index == self.buffer.len() {
+                self.refill();
+            }
+            self.index += 1;
+            self.buffer[self.index - 1]
+        }
+
+        /// Takes the first word as the high half like `rand` 0.4.
+        fn next_u64(&mut self) -> u64 {
+            let hi = self.next_u32() as u64;
+            let lo = self.next_u32() as u64;
+            (hi << 32) | lo
+        }
+    }
+
+    /// Samples an element of `F` like `rand` of `ff` 0.4, which samples limbs
+    /// of Montgomery form with unused bits masked until it is in range.
+    pub(crate) fn random_field_element<F: FieldExt>(rng: &mut ChaChaRng) -> F {
+        let num_limbs = field_repr_size::<F>() / 8;
+        let shave_bits = 64 * num_limbs as u64 - modulus::<F>().bits();
+        let mont_r_inv = mont_r::<F>().invert().unwrap();
+        loop {
+            let mut repr = F::Repr::default();
+            for (idx, limb) in repr.as_mut().chunks_mut(8).enumerate() {
+                let mut value = rng.next_u64();
+                if idx == num_limbs - 1 {
+                    value &= u64::MAX >> shave_bits;
+                }
+                limb.copy_from_slice(&value.to_le_bytes());
+            }
+            if let Some(fe) = Option::<F>::from(F::from_repr(repr)) {
+                return fe * mont_r_inv;
+            }
+        }
+    }
+
+    /// Hashes `digest` into G2 like `hash_to_g2` of the ceremony, which seeds
+    /// [`ChaChaRng`] by the first 32 bytes and samples a random point with
+    /// `x` of 2 base field elements, then clears the cofactor `2p - r` of G2
+    /// of BN curves.
+    fn hash_to_g2<M: MultiMillerLoop>(digest: &[u8]) -> M::G2Affine {
+        type G1Base<M> = <<M as Engine>::G1Affine as CurveAffine>::Base;
+        type G2Base<M> = <<M as Engine>::G2Affine as CurveAffine>::Base;
+
+        let mut seed = [0; 8];
+        for (seed, bytes) in seed.iter_mut().zip(digest.chunks(4)) {
+            *seed = u32::from_be_bytes(bytes.try_into().unwrap());
+        }
+        let mut rng = ChaChaRng::from_seed(seed);
+
+        let cofactor = 2u64 * modulus::<G1Base<M>>() - modulus::<M::Scalar>();
+        // Compare field elements in the way of the ceremony, where the higher
+        // coefficient of extension field is compared first.
+        let is_less = |lhs: &G2Base<M>, rhs: &G2Base<M>| {
+            lhs.to_repr()
+                .as_ref()
+                .iter()
+                .rev()
+                .lt(rhs.to_repr().as_ref().iter().rev())
+        };
+        loop {
+            let mut repr = <G2Base<M> as PrimeField>::Repr::default();
+            let size = field_repr_size::<G1Base<M>>();
+            for chunk in repr.as_mut().chunks_mut(size) {
+                chunk.copy_from_slice(
+                    random_field_element::<G1Base<M>>(&mut rng)
+                        .to_repr()
+                        .as_ref(),
+                );
+            }
+            let greatest = rng.next_u32() as u8 & 1 == 1;
+
+            let x = match Option::<G2Base<M>>::from(G2Base::<M>::from_repr(repr)) {
+                Some(x) => x,
+                None => continue,
+            };
+            let y = (x.square() * x + M::G2Affine::a() * x + M::G2Affine::b()).sqrt();
+            let point = Option::<G2Base<M>>::from(y).and_then(|y| {
+                let y = if is_less(&y, &-y) ^ greatest { y } else { -y };
+                Option::<M::G2Affine>::from(M::G2Affine::from_xy(x, y))
+            });
+            if let Some(point) = point {
+                let mut acc = M::G2::identity();
+                for idx in (0..cofactor.bits()).rev() {
+                    acc = acc.double();
+                    if cofactor.bit(idx) {
+                        acc += point;
+                    }
+                }
+                if !bool::from(acc.is_identity()) {
+                    return acc.to_affine();
+                }
+            }
+        }
+    }
+
+    /// Derives the G2 point to check proof of knowledge of `x` in `(s, s * x)`,
+    /// bound to `digest` of the challenge by `personalization`, which is 0 for
+    /// tau, 1 for alpha and 2 for beta.
+    pub fn compute_g2_s<M: MultiMillerLoop>(
+        digest: &[u8],
+        g1_s: &M::G1Affine,
+        g1_s_x: &M::G1Affine,
+        personalization: u8,
+    ) -> M::G2Affine {
+        let mut state = blake2b_simd::State::new();
+        state.update(&[personalization]);
+        state.update(digest);
+        state.update(&zcash::encode_uncompressed(g1_s));
+        state.update(&zcash::encode_uncompressed(g1_s_x));
+        hash_to_g2::<M>(state.finalize().as_bytes())
+    }
+
+    /// Checks `e(g1s.0, g2s.1) == e(g1s.1, g2s.0)`, and neither side is
+    /// identity.
+    pub(crate) fn same_ratio_pair<M: MultiMillerLoop>(
+        g1s: (M::G1Affine, M::G1Affine),
+        g2s: (M::G2Affine, M::G2Affine),
+    ) -> bool {
+        if bool::from(
+            g1s.0.is_identity() | g1s.1.is_identity() | g2s.0.is_identity() | g2s.1.is_identity(),
+        ) {
+            return false;
+        }
+        M::multi_miller_loop(&[(&g1s.0, &g2s.1.into()), (&-g1s.1, &g2s.0.into())])
+            .final_exponentiation()
+            .is_identity()
+            .into()
+    }
+
+    /// Checks `g2s` are successive powers with ratio `s` where
+    /// `s_g1 = g1 * s`, by a random linear combination.
+    pub(crate) fn same_ratio_g2<M: MultiMillerLoop>(
+        g2s: &[M::G2Affine],
+        g1: M::G1Affine,
+        s_g1: M::G1Affine,
+    ) -> bool {
+        let coeffs = iter::repeat_with(|| M::Scalar::random(OsRng))
+            .take(g2s.len() - 1)
+            .collect::<Vec<_>>();
+        let lhs = best_multiexp(&coeffs, &g2s[..g2s.len() - 1]);
+        let rhs = best_multiexp(&coeffs, &g2s[1..]);
+        same_ratio_pair::<M>((g1, s_g1), (lhs.to_affine(), rhs.to_affine()))
+    }
+
+    fn file_hash<R: io::Read + io::Seek>(reader: &mut R) -> Result<[u8; 64], SrsError> {
+        let mut state = blake2b_simd::State::new();
+        reader.rewind()?;
+        io::copy(reader, &mut state)?;
+        Ok(*state.finalize().as_array())
+    }
+
+    /// Verifies `response` is a valid contribution on `challenge` for `2^k`
+    /// powers, which checks:
+    ///
+    /// - Response starts with the BLAKE2b hash of the challenge
+    /// - Proofs of knowledge of tau, alpha and beta in the public key
+    /// - Accumulator is updated by the same tau, alpha and beta
+    /// - Sections of response are successive powers of tau
+    pub fn verify_transition<M: MultiMillerLoop, R1, R2>(
+        challenge: &mut R1,
+        response: &mut R2,
+        k: u32,
+    ) -> Result<(), SrsError>
+    where
+        R1: io::Read + io::Seek,
+        R2: io::Read + io::Seek,
+    {
+        let digest = file_hash(challenge)?;
+        let (_, before) = read_accumulator::<M, _>(challenge, k, Compression::Uncompressed)?;
+        let (hash, after) = read_accumulator::<M, _>(response, k, Compression::Compressed)?;
+        if hash != digest {
+            return Err(SrsError::ValidationFailed("hash chain"));
+        }
+        let key = read_public_key::<M, _>(response, k)?;
+
+        let tau_g2_s = compute_g2_s::<M>(&digest, &key.tau_g1.0, &key.tau_g1.1, 0);
+        let alpha_g2_s = compute_g2_s::<M>(&digest, &key.alpha_g1.0, &key.alpha_g1.1, 1);
+        let beta_g2_s = compute_g2_s::<M>(&digest, &key.beta_g1.0, &key.beta_g1.1, 2);
+        if !same_ratio_pair::<M>(key.tau_g1, (tau_g2_s, key.tau_g2))
+            || !same_ratio_pair::<M>(key.alpha_g1, (alpha_g2_s, key.alpha_g2))
+            || !same_ratio_pair::<M>(key.beta_g1, (beta_g2_s, key.beta_g2))
+        {
+            return Err(SrsError::ValidationFailed("proof of knowledge"));
+        }
+
+        if after.tau_g1[0] != M::G1Affine::generator()
+            || after.tau_g2[0] != M::G2Affine::generator()
+        {
+            return Err(SrsError::ValidationFailed("generators"));
+        }
+        if !same_ratio_pair::<M>((before.tau_g1[1], after.tau_g1[1]), (tau_g2_s, key.tau_g2))
+            || !same_ratio_pair::<M>(
+                (before.alpha_tau_g1[0], after.alpha_tau_g1[0]),
+                (alpha_g2_s, key.alpha_g2),
+            )
+            || !same_ratio_pair::<M>(
+                (before.beta_tau_g1[0], after.beta_tau_g1[0]),
+                (beta_g2_s, key.beta_g2),
+            )
+            || !same_ratio_pair::<M>(key.beta_g1, (before.beta_g2, after.beta_g2))
+        {
+            return Err(SrsError::ValidationFailed("update by public key"));
+        }
+
+        let (g2, s_g2) = (after.tau_g2[0], after.tau_g2[1]);
+        if !same_ratio::<M>(&after.tau_g1, g2, s_g2)
+            || !same_ratio_g2::<M>(&after.tau_g2, after.tau_g1[0], after.tau_g1[1])
+            || !same_ratio::<M>(&after.alpha_tau_g1, g2, s_g2)
+            || !same_ratio::<M>(&after.beta_tau_g1, g2, s_g2)
+        {
+            return Err(SrsError::ValidationFailed("same ratio"));
+        }
+
+        Ok(())
+    }
+
+    /// Verifies a chain of `(challenge, response)` for `2^k` powers by
+    /// [`verify_transition`], and that each challenge is the uncompressed
+    /// previous response with its hash, then returns
+    /// [`SrsError::InvalidContribution`] with the index of the first broken
+    /// one.
+    pub fn verify_chain<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        files: &mut [(R, R)],
+        k: u32,
+    ) -> Result<(), SrsError> {
+        let to_invalid_contribution = |index| {
+            move |err| match err {
+                SrsError::ValidationFailed(check) => SrsError::InvalidContribution { index, check },
+                err => err,
+            }
+        };
+        for index in 0..files.len() {
+            if index > 0 {
+                let digest = file_hash(&mut files[index - 1].1)?;
+                let (_, previous) =
+                    read_accumulator::<M, _>(&mut files[index - 1].1, k, Compression::Compressed)?;
+                let (hash, current) =
+                    read_accumulator::<M, _>(&mut files[index].0, k, Compression::Uncompressed)?;
+                if hash != digest || previous != current {
+                    return Err(SrsError::InvalidContribution {
+                        index,
+                        check: "challenge from previous response",
+                    });
+                }
+            }
+            let (challenge, response) = &mut files[index];
+            verify_transition::<M, _, _>(challenge, response, k)
+                .map_err(to_invalid_contribution(index))?;
+        }
+        Ok(())
     }
 
     pub fn read_g1s<M: MultiMillerLoop, R: io::Read + io::Seek, const IN_PLACE: bool>(
         reader: &mut R,
         n: usize,
-    ) -> Vec<M::G1Affine> {
+        compression: Compression,
+    ) -> Result<Vec<M::G1Affine>, SrsError> {
         if !IN_PLACE {
-            seek_g1_offset(reader);
+            seek_g1_offset(reader)?;
         }
-        read_ec_points::<M::G1Affine, _>(reader, n)
+        read_ec_points::<M::G1Affine, _>(reader, n, compression, "tau_g1")
     }
 
     pub fn read_g2s<M: MultiMillerLoop, R: io::Read + io::Seek, const IN_PLACE: bool>(
         reader: &mut R,
         k: u32,
         n: usize,
-    ) -> Vec<M::G2Affine> {
+        compression: Compression,
+    ) -> Result<Vec<M::G2Affine>, SrsError> {
         if !IN_PLACE {
-            seek_g2_offset::<M, _>(reader, k);
+            seek_g2_offset::<M, _>(reader, k, compression)?;
+        }
+        read_ec_points::<M::G2Affine, _>(reader, n, compression, "tau_g2")
+    }
+
+    /// Challenge or response of Perpetual Powers of Tau with `2^k` powers.
+    /// Responses are compressed by default, and challenges are uncompressed.
+    #[derive(Clone, Copy, Debug)]
+    pub struct PerpetualPowersOfTau {
+        pub k: u32,
+        pub compression: Compression,
+    }
+
+    impl<M: MultiMillerLoop> SrsReader<M> for PerpetualPowersOfTau {
+        fn read_k<R: io::Read + io::Seek>(&self, _: &mut R) -> Result<u32, SrsError> {
+            Ok(self.k)
+        }
+
+        fn read_g1s<R: io::Read + io::Seek>(
+            &self,
+            reader: &mut R,
+            range: Range<usize>,
+        ) -> Result<Vec<M::G1Affine>, SrsError> {
+            let g1_size = ec_point_size::<M::G1Affine>(self.compression);
+            let offset = G1_OFFSET + (range.start * g1_size) as u64;
+            reader.seek(io::SeekFrom::Start(offset))?;
+            read_g1s::<M, _, true>(reader, range.len(), self.compression)
+        }
+
+        fn read_g2s<R: io::Read + io::Seek>(
+            &self,
+            reader: &mut R,
+        ) -> Result<[M::G2Affine; 2], SrsError> {
+            Ok(
+                read_g2s::<M, _, false>(reader, self.k, 2, self.compression)?
+                    .try_into()
+                    .unwrap(),
+            )
+        }
+    }
+
+    impl<M: MultiMillerLoop> SrsWriter<M> for PerpetualPowersOfTau {
+        fn write<W: io::Write>(
+            &self,
+            writer: &mut W,
+            k: u32,
+            g: &[M::G1Affine],
+            _: &[M::G1Affine],
+            g2s: [M::G2Affine; 2],
+        ) -> Result<(), SrsError> {
+            check_desired_k(k, self.k)?;
+            write::<M, _>(writer, self.k, g, &g2s, self.compression)
         }
-        read_ec_points::<M::G2Affine, _>(reader, n)
     }
 }
 
-pub mod snarkjs {
+pub mod aztec_ignition {
     use crate::{
-        arithmetic::parallelize,
-        util::{field_repr_size, mont_r},
+        util::{decode_ec_points, field_repr_size},
+        SrsError, SrsReader,
     };
-    use byteorder::{LittleEndian, ReadBytesExt};
+    use byteorder::{BigEndian, ReadBytesExt};
     use halo2_curves::{
-        group::ff::{Field, PrimeField},
+        group::{ff::PrimeField, prime::PrimeCurveAffine},
         pairing::MultiMillerLoop,
         CurveAffine,
     };
-    use std::io;
+    use std::{
+        fs::File,
+        io,
+        ops::Range,
+        path::{Path, PathBuf},
+    };
 
-    pub const HEADER_SIZE_OFFSET: u64 = 16;
-    pub const HEADER_OFFSET: u64 = HEADER_SIZE_OFFSET + 8;
+    pub const MANIFEST_SIZE: u64 = 28;
+    pub const G1_OFFSET: u64 = MANIFEST_SIZE;
 
-    pub fn read_header_size<R: io::Read + io::Seek>(reader: &mut R) -> u64 {
-        reader
-            .seek(io::SeekFrom::Start(HEADER_SIZE_OFFSET))
-            .unwrap();
-        reader.read_u64::<LittleEndian>().unwrap()
+    #[derive(Clone, Copy, Debug)]
+    pub struct Manifest {
+        pub transcript_number: u32,
+        pub total_transcripts: u32,
+        pub total_g1_points: u32,
+        pub total_g2_points: u32,
+        pub num_g1_points: u32,
+        pub num_g2_points: u32,
+        pub start_from: u32,
+    }
+
+    pub fn transcript_path(dir: impl AsRef<Path>, transcript_number: u32) -> PathBuf {
+        dir.as_ref()
+            .join(format!("transcript{transcript_number:02}.dat"))
+    }
+
+    pub fn read_manifest<R: io::Read + io::Seek>(reader: &mut R) -> Result<Manifest, SrsError> {
+        reader.seek(io::SeekFrom::Start(0))?;
+        let mut fields = [0; 7];
+        for field in fields.iter_mut() {
+            *field = reader.read_u32::<BigEndian>()?;
+        }
+        let [transcript_number, total_transcripts, total_g1_points, total_g2_points, num_g1_points, num_g2_points, start_from] =
+            fields;
+        Ok(Manifest {
+            transcript_number,
+            total_transcripts,
+            total_g1_points,
+            total_g2_points,
+            num_g1_points,
+            num_g2_points,
+            start_from,
+        })
+    }
+
+    /// Returns the largest `k` such that `2^k` powers fit in the transcripts,
+    /// where the first power is the generator which is not stored.
+    pub fn read_k<R: io::Read + io::Seek>(reader: &mut R) -> Result<u32, SrsError> {
+        let n = read_manifest(reader)?.total_g1_points as u64 + 1;
+        Ok(63 - n.leading_zeros())
     }
 
-    pub fn read_k<R: io::Read + io::Seek>(reader: &mut R) -> u32 {
-        let k_offset = HEADER_OFFSET + read_header_size(reader) - 8;
-        reader.seek(io::SeekFrom::Start(k_offset)).unwrap();
-        reader.read_u32::<LittleEndian>().unwrap()
+    /// Aztec stores field elements as 64-bit limbs in little-endian order,
+    /// with each limb in big-endian.
+    fn read_field_elements<F: PrimeField, R: io::Read>(
+        reader: &mut R,
+        n: usize,
+    ) -> Result<Vec<F::Repr>, SrsError> {
+        let mut reprs = vec![F::Repr::default(); n];
+        for repr in reprs.iter_mut() {
+            reader.read_exact(repr.as_mut())?;
+            for limb in repr.as_mut().chunks_mut(8) {
+                limb.reverse();
+            }
+        }
+        Ok(reprs)
     }
 
-    pub fn read_g1_offset<R: io::Read + io::Seek>(reader: &mut R) -> u64 {
-        HEADER_OFFSET + read_header_size(reader) + 12
+    fn read_ec_points<C: CurveAffine, R: io::Read>(
+        reader: &mut R,
+        n: usize,
+        section: &'static str,
+    ) -> Result<Vec<C>, SrsError> {
+        let reprs = read_field_elements::<C::Base, _>(reader, 2 * n)?;
+
+        decode_ec_points(&reprs.chunks(2).collect::<Vec<_>>(), |index, reprs| {
+            let [x, y] = [reprs[0], reprs[1]].map(C::Base::from_repr);
+            let (x, y) = Option::from(x)
+                .zip(Option::from(y))
+                .ok_or(SrsError::InvalidPointEncoding { index, section })?;
+            Option::from(C::from_xy(x, y)).ok_or(SrsError::NotOnCurve { index, section })
+        })
+    }
+
+    /// Reads `n` powers of tau in G1 starting from the generator, opening
+    /// subsequent transcripts through `open` until enough points are read.
+    pub fn read_g1s<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        open: impl FnMut(u32) -> Result<R, SrsError>,
+        n: usize,
+    ) -> Result<Vec<M::G1Affine>, SrsError> {
+        read_g1s_range::<M, _>(open, 0..n)
+    }
+
+    /// Reads powers of tau in G1 in `range`, where the generator at 0 is not
+    /// stored, and transcripts are located by the number of points in each.
+    pub fn read_g1s_range<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        mut open: impl FnMut(u32) -> Result<R, SrsError>,
+        range: Range<usize>,
+    ) -> Result<Vec<M::G1Affine>, SrsError> {
+        let g1_size = 2 * field_repr_size::<<M::G1Affine as CurveAffine>::Base>();
+        let mut points = Vec::with_capacity(range.len());
+        if range.start == 0 && !range.is_empty() {
+            points.push(M::G1Affine::generator());
+        }
+
+        let mut transcript_number = 0;
+        let mut start = 1;
+        while range.start + points.len() < range.end {
+            let mut reader = open(transcript_number)?;
+            let manifest = read_manifest(&mut reader)?;
+            if manifest.transcript_number != transcript_number
+                || manifest.start_from as usize != start - 1
+            {
+                return Err(SrsError::UnexpectedHeader(format!(
+                    "Expected transcript {transcript_number} starting from {}, but got {manifest:?}",
+                    start - 1,
+                )));
+            }
+
+            let end = start + manifest.num_g1_points as usize;
+            let next = range.start + points.len();
+            if next < end {
+                let offset = G1_OFFSET + ((next - start) * g1_size) as u64;
+                reader.seek(io::SeekFrom::Start(offset))?;
+                let n = end.min(range.end) - next;
+                points.extend(read_ec_points::<M::G1Affine, _>(&mut reader, n, "g1")?);
+            }
+            start = end;
+            transcript_number += 1;
+        }
+        Ok(points)
     }
 
-    pub fn read_g2_offset<M: MultiMillerLoop, R: io::Read + io::Seek>(reader: &mut R) -> u64 {
-        let base_size = field_repr_size::<<M::G1Affine as CurveAffine>::Base>();
-        read_g1_offset(reader) + (2 * base_size * (2 * (1 << read_k(reader)) - 1)) as u64 + 12
+    /// Reads the generator and `[x]_2` from the first transcript.
+    pub fn read_g2s<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        reader: &mut R,
+    ) -> Result<Vec<M::G2Affine>, SrsError> {
+        let manifest = read_manifest(reader)?;
+        if manifest.transcript_number != 0 {
+            return Err(SrsError::UnexpectedHeader(format!(
+                "Expected transcript 0 to contain G2 points, but got {manifest:?}",
+            )));
+        }
+
+        let g1_size = 2 * field_repr_size::<<M::G1Affine as CurveAffine>::Base>() as u64;
+        let offset = G1_OFFSET + g1_size * manifest.num_g1_points as u64;
+        reader.seek(io::SeekFrom::Start(offset))?;
+
+        let mut points = read_ec_points::<M::G2Affine, _>(reader, 1, "g2")?;
+        points.insert(0, M::G2Affine::generator());
+        Ok(points)
     }
 
-    fn seek_g1_offset<R: io::Read + io::Seek>(reader: &mut R) {
-        let offset = read_g1_offset(reader);
-        reader.seek(io::SeekFrom::Start(offset)).unwrap();
+    /// Transcripts of Aztec Ignition in `dir`, where the reader should be the
+    /// first transcript.
+    #[derive(Clone, Debug)]
+    pub struct AztecIgnition {
+        pub dir: PathBuf,
     }
 
-    fn seek_g2_offset<M: MultiMillerLoop, R: io::Read + io::Seek>(reader: &mut R) {
-        let offset = read_g2_offset::<M, _>(reader);
-        reader.seek(io::SeekFrom::Start(offset)).unwrap();
+    impl<M: MultiMillerLoop> SrsReader<M> for AztecIgnition {
+        fn read_k<R: io::Read + io::Seek>(&self, reader: &mut R) -> Result<u32, SrsError> {
+            read_k(reader)
+        }
+
+        fn read_g1s<R: io::Read + io::Seek>(
+            &self,
+            _: &mut R,
+            range: Range<usize>,
+        ) -> Result<Vec<M::G1Affine>, SrsError> {
+            read_g1s_range::<M, _>(
+                |transcript_number| Ok(File::open(transcript_path(&self.dir, transcript_number))?),
+                range,
+            )
+        }
+
+        fn read_g2s<R: io::Read + io::Seek>(
+            &self,
+            reader: &mut R,
+        ) -> Result<[M::G2Affine; 2], SrsError> {
+            Ok(read_g2s::<M, _>(reader)?.try_into().unwrap())
+        }
+    }
+}
+
+pub mod snarkjs {
+    use crate::{
+        arithmetic, check_desired_k,
+        util::{
+            decode_ec_points, field_repr_size, modulus, mont_r,
+            perpetual_powers_of_tau::{
+                self, compute_g2_s, random_field_element, same_ratio_g2, same_ratio_pair,
+                Accumulator, ChaChaRng, PublicKey,
+            },
+            zcash, Compression,
+        },
+        SrsError, SrsReader, SrsWriter,
+    };
+    use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+    use halo2_curves::{
+        group::{
+            ff::{Field, PrimeField},
+            prime::PrimeCurveAffine,
+            Curve,
+        },
+        pairing::{Engine, MultiMillerLoop},
+        Coordinates, CurveAffine, FieldExt,
+    };
+    use num_bigint::BigUint;
+    use sha2::{Digest, Sha256};
+    use std::{io, iter, ops::Range};
+
+    pub const MAGIC: [u8; 4] = *b"ptau";
+    pub const SECTIONS_OFFSET: u64 = 12;
+    pub const SECTION_HEADER_SIZE: u64 = 12;
+    pub const HEADER_SECTION_ID: u32 = 1;
+    pub const TAU_G1_SECTION_ID: u32 = 2;
+    pub const TAU_G2_SECTION_ID: u32 = 3;
+    pub const ALPHA_TAU_G1_SECTION_ID: u32 = 4;
+    pub const BETA_TAU_G1_SECTION_ID: u32 = 5;
+    pub const BETA_G2_SECTION_ID: u32 = 6;
+    pub const CONTRIBUTIONS_SECTION_ID: u32 = 7;
+    /// Section written by `snarkjs powersoftau prepare phase2`, which contains
+    /// tauG1 in Lagrange basis for each `2^k` with `0 <= k <= power + 1`.
+    pub const LAGRANGE_G1_SECTION_ID: u32 = 12;
+    /// Same as [`LAGRANGE_G1_SECTION_ID`] but for tauG2, alphaTauG1 and
+    /// betaTauG1, with `0 <= k <= power`.
+    pub const LAGRANGE_G2_SECTION_ID: u32 = 13;
+    pub const LAGRANGE_ALPHA_TAU_G1_SECTION_ID: u32 = 14;
+    pub const LAGRANGE_BETA_TAU_G1_SECTION_ID: u32 = 15;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct Section {
+        pub id: u32,
+        /// Offset of the section data, right after the section header.
+        pub offset: u64,
+        pub size: u64,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Header {
+        pub version: u32,
+        pub modulus: BigUint,
+        pub power: u32,
+        pub ceremony_power: u32,
+        pub sections: Vec<Section>,
+    }
+
+    impl Header {
+        pub fn section(&self, id: u32) -> Option<&Section> {
+            self.sections.iter().find(|section| section.id == id)
+        }
+
+        /// Returns whether the field modulus matches the base field of `M`.
+        pub fn is_curve<M: MultiMillerLoop>(&self) -> bool {
+            self.modulus == modulus::<<M::G1Affine as CurveAffine>::Base>()
+        }
+
+        fn required_section(&self, id: u32) -> Result<&Section, SrsError> {
+            self.section(id)
+                .ok_or_else(|| SrsError::UnexpectedHeader(format!("missing section {id}")))
+        }
+    }
+
+    /// Reads id and size of a section, which prefix every section data.
+    pub fn read_section_header<R: io::Read>(reader: &mut R) -> Result<(u32, u64), SrsError> {
+        let id = reader.read_u32::<LittleEndian>()?;
+        let size = reader.read_u64::<LittleEndian>()?;
+        Ok((id, size))
+    }
+
+    /// Reads magic, version and the section table, then reads the header
+    /// section and checks the field modulus matches the one of `M`.
+    pub fn read_header<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        reader: &mut R,
+    ) -> Result<Header, SrsError> {
+        let header = read_header_unchecked(reader)?;
+        if !header.is_curve::<M>() {
+            return Err(SrsError::UnexpectedHeader(format!(
+                "Expected field modulus 0x{:x} but got 0x{:x}",
+                modulus::<<M::G1Affine as CurveAffine>::Base>(),
+                header.modulus
+            )));
+        }
+        Ok(header)
+    }
+
+    /// Reads the header like [`read_header`] without checking the field
+    /// modulus, which can be used to find out the curve of the file.
+    pub fn read_header_unchecked<R: io::Read + io::Seek>(
+        reader: &mut R,
+    ) -> Result<Header, SrsError> {
+        reader.rewind()?;
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(SrsError::UnexpectedHeader(format!(
+                "Expected magic {MAGIC:?} but got {magic:?}"
+            )));
+        }
+        let version = reader.read_u32::<LittleEndian>()?;
+        let num_sections = reader.read_u32::<LittleEndian>()?;
+
+        let mut sections = Vec::with_capacity(num_sections as usize);
+        let mut offset = SECTIONS_OFFSET;
+        for _ in 0..num_sections {
+            reader.seek(io::SeekFrom::Start(offset))?;
+            let (id, size) = read_section_header(reader)?;
+            offset += SECTION_HEADER_SIZE;
+            sections.push(Section { id, offset, size });
+            offset += size;
+        }
+
+        let header_section = sections
+            .iter()
+            .find(|section| section.id == HEADER_SECTION_ID)
+            .ok_or_else(|| {
+                SrsError::UnexpectedHeader(format!("missing section {HEADER_SECTION_ID}"))
+            })?;
+        reader.seek(io::SeekFrom::Start(header_section.offset))?;
+        let n8 = reader.read_u32::<LittleEndian>()?;
+        let mut q = vec![0; n8 as usize];
+        reader.read_exact(&mut q)?;
+        let q = BigUint::from_bytes_le(&q);
+        let power = reader.read_u32::<LittleEndian>()?;
+        let ceremony_power = reader.read_u32::<LittleEndian>()?;
+
+        Ok(Header {
+            version,
+            modulus: q,
+            power,
+            ceremony_power,
+            sections,
+        })
+    }
+
+    pub fn read_k<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        reader: &mut R,
+    ) -> Result<u32, SrsError> {
+        Ok(read_header::<M, _>(reader)?.power)
+    }
+
+    pub fn read_g1_offset<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        reader: &mut R,
+    ) -> Result<u64, SrsError> {
+        let header = read_header::<M, _>(reader)?;
+        Ok(header.required_section(TAU_G1_SECTION_ID)?.offset)
+    }
+
+    pub fn read_g2_offset<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        reader: &mut R,
+    ) -> Result<u64, SrsError> {
+        let header = read_header::<M, _>(reader)?;
+        Ok(header.required_section(TAU_G2_SECTION_ID)?.offset)
+    }
+
+    fn seek_g1_offset<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        reader: &mut R,
+    ) -> Result<(), SrsError> {
+        let offset = read_g1_offset::<M, _>(reader)?;
+        reader.seek(io::SeekFrom::Start(offset))?;
+        Ok(())
+    }
+
+    fn seek_g2_offset<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        reader: &mut R,
+    ) -> Result<(), SrsError> {
+        let offset = read_g2_offset::<M, _>(reader)?;
+        reader.seek(io::SeekFrom::Start(offset))?;
+        Ok(())
     }
 
     pub fn read_g1s<M: MultiMillerLoop, R: io::Read + io::Seek, const IN_PLACE: bool>(
         reader: &mut R,
         n: usize,
-    ) -> Vec<M::G1Affine> {
+    ) -> Result<Vec<M::G1Affine>, SrsError> {
         if !IN_PLACE {
-            seek_g1_offset(reader);
+            seek_g1_offset::<M, _>(reader)?;
         }
+        read_g1_points::<M, _>(reader, n, "tau_g1")
+    }
+
+    /// Returns whether coordinates are all zeros, which is how snarkjs
+    /// encodes identity.
+    fn is_zeros<T: AsRef<[u8]>>(reprs: &[T; 2]) -> bool {
+        reprs
+            .iter()
+            .all(|repr| repr.as_ref().iter().all(|byte| *byte == 0))
+    }
 
+    fn read_g1_points<M: MultiMillerLoop, R: io::Read>(
+        reader: &mut R,
+        n: usize,
+        section: &'static str,
+    ) -> Result<Vec<M::G1Affine>, SrsError> {
         let mut reprs =
-            vec![<<M::G1Affine as CurveAffine>::Base as PrimeField>::Repr::default(); 2 * n];
+            vec![[<<M::G1Affine as CurveAffine>::Base as PrimeField>::Repr::default(); 2]; n];
         for repr in reprs.iter_mut() {
-            reader.read_exact(repr.as_mut()).unwrap();
+            reader.read_exact(repr[0].as_mut())?;
+            reader.read_exact(repr[1].as_mut())?;
         }
 
         let mont_r_inv = mont_r::<<M::G1Affine as CurveAffine>::Base>()
             .invert()
             .unwrap();
-        let mut points = vec![M::G1Affine::default(); n];
-        parallelize(&mut points, |points, start| {
-            for (i, point) in points.iter_mut().enumerate() {
-                let x = <M::G1Affine as CurveAffine>::Base::from_repr(reprs[2 * (start + i)])
-                    .unwrap()
-                    * mont_r_inv;
-                let y = <M::G1Affine as CurveAffine>::Base::from_repr(reprs[2 * (start + i) + 1])
-                    .unwrap()
-                    * mont_r_inv;
-                *point = M::G1Affine::from_xy(x, y).unwrap();
+        decode_ec_points(&reprs, |index, reprs| {
+            if is_zeros(reprs) {
+                return Ok(M::G1Affine::identity());
+            }
+            let [x, y] = reprs.map(|repr| {
+                Option::from(<M::G1Affine as CurveAffine>::Base::from_repr(repr))
+                    .map(|base: <M::G1Affine as CurveAffine>::Base| base * mont_r_inv)
+            });
+            let (x, y) = x
+                .zip(y)
+                .ok_or(SrsError::InvalidPointEncoding { index, section })?;
+            Option::from(M::G1Affine::from_xy(x, y)).ok_or(SrsError::NotOnCurve { index, section })
+        })
+    }
+
+    /// Reads `g_lagrange` with `2^k` points from a prepared file, or returns
+    /// `None` if the file is not prepared.
+    ///
+    /// snarkjs uses the smallest quadratic non-residue to derive the root of
+    /// unity, which differs from the one used by halo2, so the points are
+    /// reordered to match the halo2 domain.
+    pub fn read_g1s_lagrange<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        reader: &mut R,
+        k: u32,
+    ) -> Result<Option<Vec<M::G1Affine>>, SrsError> {
+        let offset = match read_header::<M, _>(reader)?.section(LAGRANGE_G1_SECTION_ID) {
+            Some(section) => section.offset,
+            None => return Ok(None),
+        };
+
+        let n = 1 << k;
+        let g1_size = 2 * field_repr_size::<<M::G1Affine as CurveAffine>::Base>();
+        reader.seek(io::SeekFrom::Start(offset + ((n - 1) * g1_size) as u64))?;
+        let points = read_g1s::<M, _, true>(reader, n)?;
+        Ok(Some(reorder_lagrange(points, k)))
+    }
+
+    /// Reorders `2^k` points in Lagrange basis over the domain of snarkjs to
+    /// the one of halo2.
+    fn reorder_lagrange<C: CurveAffine>(points: Vec<C>, k: u32) -> Vec<C> {
+        let n = points.len();
+        let m = log_root_of_unity(
+            arithmetic::root_of_unity::<C::Scalar>(k),
+            root_of_unity::<C::Scalar>(k),
+            k,
+        );
+        let mut g_lagrange = vec![C::default(); n];
+        for (i, point) in points.into_iter().enumerate() {
+            g_lagrange[(m * i) & (n - 1)] = point;
+        }
+        g_lagrange
+    }
+
+    /// Returns the `2^k`-th root of unity used by snarkjs, which is derived
+    /// from the smallest quadratic non-residue to the power of `t`, where
+    /// `p - 1 = 2^S * t`.
+    fn root_of_unity<F: FieldExt>(k: u32) -> F {
+        let qnr = (2u64..)
+            .map(F::from)
+            .find(|value| bool::from(value.sqrt().is_none()))
+            .unwrap();
+        let t = (modulus::<F>() - 1u64) >> F::S;
+        let mut omega = qnr.pow_vartime(t.to_u64_digits());
+        for _ in k..F::S {
+            omega = omega.square();
+        }
+        omega
+    }
+
+    /// Returns `m` such that `omega^m = target`, where both have order `2^k`.
+    fn log_root_of_unity<F: Field>(omega: F, target: F, k: u32) -> usize {
+        let omega_inv = omega.invert().unwrap();
+        let mut m = 0;
+        let mut acc = target;
+        for i in 0..k {
+            let mut acc_pow = acc;
+            for _ in i + 1..k {
+                acc_pow = acc_pow.square();
+            }
+            if acc_pow != F::one() {
+                m |= 1 << i;
+                acc *= omega_inv.pow_vartime(&[1 << i]);
+            }
+        }
+        m
+    }
+
+    /// Writes `n` points with coordinates in little-endian Montgomery form
+    /// of `B`, which is the base field of G1. Points beyond `points` are
+    /// written as zeros, which is how snarkjs encodes identity.
+    fn write_ec_points<B: FieldExt, C: CurveAffine, W: io::Write>(
+        writer: &mut W,
+        points: &[C],
+        n: usize,
+    ) -> Result<(), SrsError> {
+        let base_size = field_repr_size::<B>();
+        let mont_r = mont_r::<B>();
+        for index in 0..n {
+            let coordinates = points
+                .get(index)
+                .and_then(|point| Option::<Coordinates<C>>::from(point.coordinates()));
+            let coordinates = match coordinates {
+                Some(coordinates) => coordinates,
+                None => {
+                    writer.write_all(&vec![0; 2 * field_repr_size::<C::Base>()])?;
+                    continue;
+                }
+            };
+            for coordinate in [coordinates.x(), coordinates.y()] {
+                for chunk in coordinate.to_repr().as_ref().chunks(base_size) {
+                    let mut repr = B::Repr::default();
+                    repr.as_mut().copy_from_slice(chunk);
+                    let base = B::from_repr(repr).unwrap() * mont_r;
+                    writer.write_all(base.to_repr().as_ref())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_section_header<W: io::Write>(
+        writer: &mut W,
+        id: u32,
+        size: usize,
+    ) -> Result<(), SrsError> {
+        writer.write_u32::<LittleEndian>(id)?;
+        writer.write_u64::<LittleEndian>(size as u64)?;
+        Ok(())
+    }
+
+    /// Writes a ptau file for `2^k` powers with `g1s` as tauG1 and `g2s` as
+    /// tauG2. Missing powers, alphaTauG1, betaTauG1 and betaG2 are written as
+    /// identity, and the contributions section is written with no
+    /// contribution, which marks the file as not coming from a ceremony.
+    pub fn write<M: MultiMillerLoop, W: io::Write>(
+        writer: &mut W,
+        k: u32,
+        g1s: &[M::G1Affine],
+        g2s: &[M::G2Affine],
+    ) -> Result<(), SrsError> {
+        let accumulator = Accumulator::<M> {
+            tau_g1: g1s.to_vec(),
+            tau_g2: g2s.to_vec(),
+            alpha_tau_g1: Vec::new(),
+            beta_tau_g1: Vec::new(),
+            beta_g2: M::G2Affine::identity(),
+        };
+        write_accumulator(writer, k, &accumulator, &[])
+    }
+
+    /// Writes a ptau file for `2^k` powers with all sections of
+    /// `accumulator`, and `contributions` made to it. Missing points are
+    /// written as identity.
+    pub fn write_accumulator<M: MultiMillerLoop, W: io::Write>(
+        writer: &mut W,
+        k: u32,
+        accumulator: &Accumulator<M>,
+        contributions: &[Contribution<M>],
+    ) -> Result<(), SrsError> {
+        type Base<M> = <<M as Engine>::G1Affine as CurveAffine>::Base;
+
+        let n = 1 << k;
+        let n8 = field_repr_size::<Base<M>>();
+        let g1_size = 2 * n8;
+        let g2_size = 2 * field_repr_size::<<M::G2Affine as CurveAffine>::Base>();
+
+        writer.write_all(&MAGIC)?;
+        writer.write_u32::<LittleEndian>(1)?;
+        writer.write_u32::<LittleEndian>(7)?;
+
+        write_section_header(writer, HEADER_SECTION_ID, 4 + n8 + 8)?;
+        writer.write_u32::<LittleEndian>(n8 as u32)?;
+        let mut q = modulus::<Base<M>>().to_bytes_le();
+        q.resize(n8, 0);
+        writer.write_all(&q)?;
+        writer.write_u32::<LittleEndian>(k)?;
+        writer.write_u32::<LittleEndian>(k)?;
+
+        write_section_header(writer, TAU_G1_SECTION_ID, (2 * n - 1) * g1_size)?;
+        write_ec_points::<Base<M>, _, _>(writer, &accumulator.tau_g1, 2 * n - 1)?;
+        write_section_header(writer, TAU_G2_SECTION_ID, n * g2_size)?;
+        write_ec_points::<Base<M>, _, _>(writer, &accumulator.tau_g2, n)?;
+        write_section_header(writer, ALPHA_TAU_G1_SECTION_ID, n * g1_size)?;
+        write_ec_points::<Base<M>, _, _>(writer, &accumulator.alpha_tau_g1, n)?;
+        write_section_header(writer, BETA_TAU_G1_SECTION_ID, n * g1_size)?;
+        write_ec_points::<Base<M>, _, _>(writer, &accumulator.beta_tau_g1, n)?;
+        write_section_header(writer, BETA_G2_SECTION_ID, g2_size)?;
+        write_ec_points::<Base<M>, _, _>(writer, &[accumulator.beta_g2], 1)?;
+
+        let mut section = Vec::new();
+        section.write_u32::<LittleEndian>(contributions.len() as u32)?;
+        for contribution in contributions {
+            write_contribution(&mut section, contribution)?;
+        }
+        write_section_header(writer, CONTRIBUTIONS_SECTION_ID, section.len())?;
+        writer.write_all(&section)?;
+
+        Ok(())
+    }
+
+    /// Reads all sections truncated to `2^desired_k` powers, except tauG1
+    /// truncated to `2^(desired_k+1) - 1` powers, and tauG2 has at least 2
+    /// powers. Files with power 0 are rejected, as they have no tauG2 beyond
+    /// the generator.
+    pub fn read_sections<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        reader: &mut R,
+        desired_k: u32,
+    ) -> Result<Accumulator<M>, SrsError> {
+        let header = read_header::<M, _>(reader)?;
+        if header.power == 0 {
+            return Err(SrsError::UnexpectedHeader(
+                "Expected power of at least 1 but got 0".to_string(),
+            ));
+        }
+        check_desired_k(desired_k, header.power)?;
+        let n = 1 << desired_k;
+
+        let seek = |reader: &mut R, id| {
+            let offset = header.required_section(id)?.offset;
+            reader.seek(io::SeekFrom::Start(offset))?;
+            Ok::<_, SrsError>(())
+        };
+        seek(reader, TAU_G1_SECTION_ID)?;
+        let tau_g1 = read_g1_points::<M, _>(reader, 2 * n - 1, "tau_g1")?;
+        seek(reader, TAU_G2_SECTION_ID)?;
+        let tau_g2 = read_g2_points::<M, _>(reader, n.max(2), "tau_g2")?;
+        seek(reader, ALPHA_TAU_G1_SECTION_ID)?;
+        let alpha_tau_g1 = read_g1_points::<M, _>(reader, n, "alpha_tau_g1")?;
+        seek(reader, BETA_TAU_G1_SECTION_ID)?;
+        let beta_tau_g1 = read_g1_points::<M, _>(reader, n, "beta_tau_g1")?;
+        seek(reader, BETA_G2_SECTION_ID)?;
+        let beta_g2 = read_g2_points::<M, _>(reader, 1, "beta_g2")?[0];
+
+        Ok(Accumulator {
+            tau_g1,
+            tau_g2,
+            alpha_tau_g1,
+            beta_tau_g1,
+            beta_g2,
+        })
+    }
+
+    /// Parameters of a random beacon contribution, where the secrets are
+    /// derived from `hash` iterated `2^num_iterations_exp` times.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct Beacon {
+        pub hash: Vec<u8>,
+        pub num_iterations_exp: u8,
+    }
+
+    /// Contribution recorded in the contributions section, where the points
+    /// are the first non-trivial ones of each section right after it.
+    #[derive(Clone, Debug)]
+    pub struct Contribution<M: MultiMillerLoop> {
+        pub tau_g1: M::G1Affine,
+        pub tau_g2: M::G2Affine,
+        pub alpha_g1: M::G1Affine,
+        pub beta_g1: M::G1Affine,
+        pub beta_g2: M::G2Affine,
+        pub key: PublicKey<M>,
+        /// Internal state of BLAKE2b after hashing the response without the
+        /// public key.
+        pub partial_hash: Vec<u8>,
+        /// Hash of the challenge to the next contributor.
+        pub next_challenge: [u8; 64],
+        pub name: Option<String>,
+        pub beacon: Option<Beacon>,
+    }
+
+    const PARTIAL_HASH_SIZE: usize = 216;
+    const CONTRIBUTION_TYPE_BEACON: u32 = 1;
+    const PARAMETER_NAME: u8 = 1;
+    const PARAMETER_NUM_ITERATIONS_EXP: u8 = 2;
+    const PARAMETER_BEACON_HASH: u8 = 3;
+
+    fn read_contribution<M: MultiMillerLoop, R: io::Read>(
+        reader: &mut R,
+    ) -> Result<Contribution<M>, SrsError> {
+        let tau_g1 = read_g1_points::<M, _>(reader, 1, "contribution_tau_g1")?[0];
+        let tau_g2 = read_g2_points::<M, _>(reader, 1, "contribution_tau_g2")?[0];
+        let alpha_g1 = read_g1_points::<M, _>(reader, 1, "contribution_alpha_g1")?[0];
+        let beta_g1 = read_g1_points::<M, _>(reader, 1, "contribution_beta_g1")?[0];
+        let beta_g2 = read_g2_points::<M, _>(reader, 1, "contribution_beta_g2")?[0];
+        let g1s = read_g1_points::<M, _>(reader, 6, "contribution_key_g1")?;
+        let g2s = read_g2_points::<M, _>(reader, 3, "contribution_key_g2")?;
+        let key = PublicKey {
+            tau_g1: (g1s[0], g1s[1]),
+            alpha_g1: (g1s[2], g1s[3]),
+            beta_g1: (g1s[4], g1s[5]),
+            tau_g2: g2s[0],
+            alpha_g2: g2s[1],
+            beta_g2: g2s[2],
+        };
+        let mut partial_hash = vec![0; PARTIAL_HASH_SIZE];
+        reader.read_exact(&mut partial_hash)?;
+        let mut next_challenge = [0; 64];
+        reader.read_exact(&mut next_challenge)?;
+        let contribution_type = reader.read_u32::<LittleEndian>()?;
+
+        let mut parameters = vec![0; reader.read_u32::<LittleEndian>()? as usize];
+        reader.read_exact(&mut parameters)?;
+        let mut parameters = parameters.as_slice();
+        let (mut name, mut hash, mut num_iterations_exp) = (None, None, None);
+        while !parameters.is_empty() {
+            match parameters.read_u8()? {
+                PARAMETER_NAME => {
+                    let mut bytes = vec![0; parameters.read_u8()? as usize];
+                    io::Read::read_exact(&mut parameters, &mut bytes)?;
+                    name = Some(String::from_utf8_lossy(&bytes).into_owned());
+                }
+                PARAMETER_NUM_ITERATIONS_EXP => num_iterations_exp = Some(parameters.read_u8()?),
+                PARAMETER_BEACON_HASH => {
+                    let mut bytes = vec![0; parameters.read_u8()? as usize];
+                    io::Read::read_exact(&mut parameters, &mut bytes)?;
+                    hash = Some(bytes);
+                }
+                key => {
+                    return Err(SrsError::UnexpectedHeader(format!(
+                        "Unknown contribution parameter {key}"
+                    )))
+                }
+            }
+        }
+        let beacon = match (contribution_type, hash, num_iterations_exp) {
+            (CONTRIBUTION_TYPE_BEACON, Some(hash), Some(num_iterations_exp)) => Some(Beacon {
+                hash,
+                num_iterations_exp,
+            }),
+            (CONTRIBUTION_TYPE_BEACON, _, _) => {
+                return Err(SrsError::UnexpectedHeader(
+                    "Missing beacon parameters".to_string(),
+                ))
+            }
+            _ => None,
+        };
+
+        Ok(Contribution {
+            tau_g1,
+            tau_g2,
+            alpha_g1,
+            beta_g1,
+            beta_g2,
+            key,
+            partial_hash,
+            next_challenge,
+            name,
+            beacon,
+        })
+    }
+
+    fn write_contribution<M: MultiMillerLoop, W: io::Write>(
+        writer: &mut W,
+        contribution: &Contribution<M>,
+    ) -> Result<(), SrsError> {
+        type Base<M> = <<M as Engine>::G1Affine as CurveAffine>::Base;
+
+        let key = &contribution.key;
+        let g1s = [
+            contribution.tau_g1,
+            contribution.alpha_g1,
+            contribution.beta_g1,
+            key.tau_g1.0,
+            key.tau_g1.1,
+            key.alpha_g1.0,
+            key.alpha_g1.1,
+            key.beta_g1.0,
+            key.beta_g1.1,
+        ];
+        write_ec_points::<Base<M>, _, _>(writer, &g1s[..1], 1)?;
+        write_ec_points::<Base<M>, _, _>(writer, &[contribution.tau_g2], 1)?;
+        write_ec_points::<Base<M>, _, _>(writer, &g1s[1..3], 2)?;
+        write_ec_points::<Base<M>, _, _>(writer, &[contribution.beta_g2], 1)?;
+        write_ec_points::<Base<M>, _, _>(writer, &g1s[3..], 6)?;
+        write_ec_points::<Base<M>, _, _>(writer, &[key.tau_g2, key.alpha_g2, key.beta_g2], 3)?;
+        let mut partial_hash = contribution.partial_hash.clone();
+        partial_hash.resize(PARTIAL_HASH_SIZE, 0);
+        writer.write_all(&partial_hash)?;
+        writer.write_all(&contribution.next_challenge)?;
+
+        let mut parameters = Vec::new();
+        if let Some(name) = &contribution.name {
+            parameters.push(PARAMETER_NAME);
+            parameters.push(name.len() as u8);
+            parameters.extend_from_slice(name.as_bytes());
+        }
+        if let Some(beacon) = &contribution.beacon {
+            parameters.push(PARAMETER_NUM_ITERATIONS_EXP);
+            parameters.push(beacon.num_iterations_exp);
+            parameters.push(PARAMETER_BEACON_HASH);
+            parameters.push(beacon.hash.len() as u8);
+            parameters.extend_from_slice(&beacon.hash);
+        }
+        let contribution_type = match contribution.beacon {
+            Some(_) => CONTRIBUTION_TYPE_BEACON,
+            None => 0,
+        };
+        writer.write_u32::<LittleEndian>(contribution_type)?;
+        writer.write_u32::<LittleEndian>(parameters.len() as u32)?;
+        writer.write_all(&parameters)?;
+        Ok(())
+    }
+
+    /// Reads all contributions in the contributions section.
+    pub fn read_contributions<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        reader: &mut R,
+    ) -> Result<Vec<Contribution<M>>, SrsError> {
+        let header = read_header::<M, _>(reader)?;
+        let section = header.required_section(CONTRIBUTIONS_SECTION_ID)?;
+        reader.seek(io::SeekFrom::Start(section.offset))?;
+        let num_contributions = reader.read_u32::<LittleEndian>()?;
+        (0..num_contributions)
+            .map(|_| read_contribution::<M, _>(reader))
+            .collect()
+    }
+
+    /// Returns the hash of the initial challenge for `2^power` powers, which
+    /// is the hash of an accumulator with all points as generators, and is
+    /// the one the first contributor responds to.
+    pub fn first_challenge_hash<M: MultiMillerLoop>(power: u32) -> [u8; 64] {
+        let g1 = zcash::encode_uncompressed(&M::G1Affine::generator());
+        let g2 = zcash::encode_uncompressed(&M::G2Affine::generator());
+        let n = 1 << power;
+        let mut state = blake2b_simd::State::new();
+        state.update(blake2b_simd::blake2b(&[]).as_bytes());
+        for (bytes, count) in [(&g1, 2 * n - 1), (&g2, n), (&g1, n), (&g1, n), (&g2, 1)] {
+            for _ in 0..count {
+                state.update(bytes);
             }
-        });
-        points
+        }
+        *state.finalize().as_array()
+    }
+
+    /// Derives the secrets of tau, alpha and beta of a beacon contribution
+    /// like `snarkjs powersoftau beacon`, which seeds [`ChaChaRng`] by `hash`
+    /// iterated `2^num_iterations_exp` times with SHA-256, or returns `None`
+    /// if the number of iterations overflows.
+    fn beacon_secrets<M: MultiMillerLoop>(beacon: &Beacon) -> Option<[M::Scalar; 3]> {
+        let num_iterations = 1u64.checked_shl(beacon.num_iterations_exp as u32)?;
+        let mut hash = beacon.hash.clone();
+        for _ in 0..num_iterations {
+            hash = Sha256::digest(&hash).to_vec();
+        }
+        let mut seed = [0; 8];
+        for (seed, bytes) in seed.iter_mut().zip(hash.chunks(4)) {
+            *seed = u32::from_be_bytes(bytes.try_into().unwrap());
+        }
+        let mut rng = ChaChaRng::from_seed(seed);
+        Some([(); 3].map(|_| random_field_element(&mut rng)))
+    }
+
+    /// Contribution verified by [`verify_contributions`].
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct ContributionReport {
+        pub index: usize,
+        pub name: Option<String>,
+        pub beacon: Option<Beacon>,
+        pub next_challenge: [u8; 64],
+    }
+
+    fn verify_contribution<M: MultiMillerLoop>(
+        prev: &Contribution<M>,
+        cur: &Contribution<M>,
+    ) -> Option<&'static str> {
+        let key = &cur.key;
+        let challenge = &prev.next_challenge;
+        let tau_g2_s = compute_g2_s::<M>(challenge, &key.tau_g1.0, &key.tau_g1.1, 0);
+        let alpha_g2_s = compute_g2_s::<M>(challenge, &key.alpha_g1.0, &key.alpha_g1.1, 1);
+        let beta_g2_s = compute_g2_s::<M>(challenge, &key.beta_g1.0, &key.beta_g1.1, 2);
+        if let Some(beacon) = &cur.beacon {
+            let secrets = match beacon_secrets::<M>(beacon) {
+                Some(secrets) => secrets,
+                None => return Some("beacon"),
+            };
+            let checks = [
+                (key.tau_g1, "beacon (tau)"),
+                (key.alpha_g1, "beacon (alpha)"),
+                (key.beta_g1, "beacon (beta)"),
+            ];
+            for (((s, s_x), check), x) in checks.into_iter().zip(secrets) {
+                if (s * x).to_affine() != s_x {
+                    return Some(check);
+                }
+            }
+        }
+        [
+            (key.tau_g1, (tau_g2_s, key.tau_g2), "key (tau)"),
+            (key.alpha_g1, (alpha_g2_s, key.alpha_g2), "key (alpha)"),
+            (key.beta_g1, (beta_g2_s, key.beta_g2), "key (beta)"),
+            ((prev.tau_g1, cur.tau_g1), (tau_g2_s, key.tau_g2), "tau_g1"),
+            (key.tau_g1, (prev.tau_g2, cur.tau_g2), "tau_g2"),
+            (
+                (prev.alpha_g1, cur.alpha_g1),
+                (alpha_g2_s, key.alpha_g2),
+                "alpha_g1",
+            ),
+            (
+                (prev.beta_g1, cur.beta_g1),
+                (beta_g2_s, key.beta_g2),
+                "beta_g1",
+            ),
+            (key.beta_g1, (prev.beta_g2, cur.beta_g2), "beta_g2"),
+        ]
+        .into_iter()
+        .find(|(g1s, g2s, _)| !same_ratio_pair::<M>(*g1s, *g2s))
+        .map(|(_, _, check)| check)
+    }
+
+    /// Verifies each contribution like `snarkjs powersoftau verify`, which
+    /// checks the proofs of knowledge, the secrets of beacons, and that the
+    /// first points are updated by the same secrets, starting from generators
+    /// with the hash of the initial challenge. Then checks the last
+    /// contribution is consistent with the accumulator in the file, all
+    /// sections are successive powers, sections in Lagrange basis if the file
+    /// is prepared, and the next challenge of the last contribution is the
+    /// hash of its response and the accumulator. The last check is skipped
+    /// for files truncated below `ceremony_power`, as the hashes cover the
+    /// untruncated accumulator.
+    ///
+    /// Returns [`SrsError::InvalidContribution`] on the first contribution
+    /// failing a check.
+    pub fn verify_contributions<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        reader: &mut R,
+    ) -> Result<Vec<ContributionReport>, SrsError> {
+        let header = read_header::<M, _>(reader)?;
+        verify_contributions_from::<M, _>(reader, first_challenge_hash::<M>(header.ceremony_power))
+    }
+
+    /// Same as [`verify_contributions`] but with the hash of the initial
+    /// challenge given, which takes long to compute for a large
+    /// `ceremony_power` since it hashes `2^(ceremony_power + 2)` points.
+    pub fn verify_contributions_from<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        reader: &mut R,
+        first_challenge_hash: [u8; 64],
+    ) -> Result<Vec<ContributionReport>, SrsError> {
+        let header = read_header::<M, _>(reader)?;
+        let contributions = read_contributions::<M, _>(reader)?;
+
+        let (g1, g2) = (M::G1Affine::generator(), M::G2Affine::generator());
+        let mut prev = Contribution::<M> {
+            tau_g1: g1,
+            tau_g2: g2,
+            alpha_g1: g1,
+            beta_g1: g1,
+            beta_g2: g2,
+            key: PublicKey {
+                tau_g1: (g1, g1),
+                alpha_g1: (g1, g1),
+                beta_g1: (g1, g1),
+                tau_g2: g2,
+                alpha_g2: g2,
+                beta_g2: g2,
+            },
+            partial_hash: Vec::new(),
+            next_challenge: first_challenge_hash,
+            name: None,
+            beacon: None,
+        };
+        let mut challenge = prev.next_challenge;
+        let mut reports = Vec::with_capacity(contributions.len());
+        for (index, cur) in contributions.into_iter().enumerate() {
+            if let Some(check) = verify_contribution(&prev, &cur) {
+                return Err(SrsError::InvalidContribution { index, check });
+            }
+            reports.push(ContributionReport {
+                index,
+                name: cur.name.clone(),
+                beacon: cur.beacon.clone(),
+                next_challenge: cur.next_challenge,
+            });
+            challenge = prev.next_challenge;
+            prev = cur;
+        }
+
+        let accumulator = read_sections::<M, _>(reader, header.power)?;
+        if prev.tau_g1 != accumulator.tau_g1[1]
+            || prev.tau_g2 != accumulator.tau_g2[1]
+            || prev.alpha_g1 != accumulator.alpha_tau_g1[0]
+            || prev.beta_g1 != accumulator.beta_tau_g1[0]
+            || prev.beta_g2 != accumulator.beta_g2
+        {
+            return Err(SrsError::ValidationFailed("final accumulator"));
+        }
+        if accumulator.tau_g1[0] != g1 || accumulator.tau_g2[0] != g2 {
+            return Err(SrsError::ValidationFailed("generators"));
+        }
+        if !arithmetic::same_ratio::<M>(&accumulator.tau_g1, g2, prev.tau_g2)
+            || !same_ratio_g2::<M>(&accumulator.tau_g2, g1, prev.tau_g1)
+            || !arithmetic::same_ratio::<M>(&accumulator.alpha_tau_g1, g2, prev.tau_g2)
+            || !arithmetic::same_ratio::<M>(&accumulator.beta_tau_g1, g2, prev.tau_g2)
+        {
+            return Err(SrsError::ValidationFailed("same ratio"));
+        }
+        verify_lagrange_sections(reader, &header, &accumulator)?;
+
+        // The response is hashed with points compressed and the public key
+        // appended, and the next challenge with points uncompressed, both in
+        // the layout of Perpetual Powers of Tau. It can only be checked when
+        // there is a contribution and the file is not truncated.
+        if reports.is_empty() || header.power != header.ceremony_power {
+            return Ok(reports);
+        }
+        let mut response = blake2b_simd::State::new();
+        perpetual_powers_of_tau::write_accumulator(
+            &mut response,
+            header.power,
+            &challenge,
+            &accumulator,
+            Compression::Compressed,
+        )?;
+        perpetual_powers_of_tau::write_public_key(&mut response, &prev.key)?;
+        let mut next_challenge = blake2b_simd::State::new();
+        perpetual_powers_of_tau::write_accumulator(
+            &mut next_challenge,
+            header.power,
+            response.finalize().as_array(),
+            &accumulator,
+            Compression::Uncompressed,
+        )?;
+        if next_challenge.finalize().as_array() != &prev.next_challenge {
+            return Err(SrsError::ValidationFailed("hash chain"));
+        }
+
+        Ok(reports)
+    }
+
+    /// Checks sections in Lagrange basis of a prepared file are the inverse
+    /// FFT of the corresponding sections for each `2^k`. The one of tauG1 has
+    /// 1 more `k` whose highest power is missing in tauG1, which is recovered
+    /// from the Lagrange basis then checked to be the next power.
+    fn verify_lagrange_sections<M: MultiMillerLoop, R: io::Read + io::Seek>(
+        reader: &mut R,
+        header: &Header,
+        accumulator: &Accumulator<M>,
+    ) -> Result<(), SrsError> {
+        let power = header.power;
+        let read_tau_g1s = |reader: &mut R, n| read_g1_points::<M, _>(reader, n, "tau_g1_lagrange");
+        if verify_lagrange_section(
+            reader,
+            header,
+            LAGRANGE_G1_SECTION_ID,
+            &accumulator.tau_g1,
+            power,
+            read_tau_g1s,
+        )? {
+            let g_lagrange = reorder_lagrange(read_tau_g1s(reader, 2 << power)?, power + 1);
+            let omega_inv = arithmetic::root_of_unity::<M::Scalar>(power + 1)
+                .invert()
+                .unwrap();
+            let coeffs = iter::successors(Some(M::Scalar::one()), |coeff| Some(*coeff * omega_inv))
+                .take(g_lagrange.len())
+                .collect::<Vec<_>>();
+            let highest = arithmetic::best_multiexp(&coeffs, &g_lagrange).to_affine();
+            let tau_g1 = [&accumulator.tau_g1[..], &[highest]].concat();
+            if !arithmetic::same_lagrange(&tau_g1, &g_lagrange)
+                || !same_ratio_pair::<M>(
+                    (*accumulator.tau_g1.last().unwrap(), highest),
+                    (accumulator.tau_g2[0], accumulator.tau_g2[1]),
+                )
+            {
+                return Err(SrsError::ValidationFailed("lagrange"));
+            }
+        }
+        verify_lagrange_section(
+            reader,
+            header,
+            LAGRANGE_G2_SECTION_ID,
+            &accumulator.tau_g2,
+            power,
+            |reader, n| read_g2_points::<M, _>(reader, n, "tau_g2_lagrange"),
+        )?;
+        verify_lagrange_section(
+            reader,
+            header,
+            LAGRANGE_ALPHA_TAU_G1_SECTION_ID,
+            &accumulator.alpha_tau_g1,
+            power,
+            |reader, n| read_g1_points::<M, _>(reader, n, "alpha_tau_g1_lagrange"),
+        )?;
+        verify_lagrange_section(
+            reader,
+            header,
+            LAGRANGE_BETA_TAU_G1_SECTION_ID,
+            &accumulator.beta_tau_g1,
+            power,
+            |reader, n| read_g1_points::<M, _>(reader, n, "beta_tau_g1_lagrange"),
+        )?;
+        Ok(())
+    }
+
+    /// Checks the section `id` in Lagrange basis for each `2^k` with
+    /// `0 <= k <= max_k`, and returns whether the section exists.
+    fn verify_lagrange_section<C: CurveAffine, R: io::Read + io::Seek>(
+        reader: &mut R,
+        header: &Header,
+        id: u32,
+        powers: &[C],
+        max_k: u32,
+        read_points: impl Fn(&mut R, usize) -> Result<Vec<C>, SrsError>,
+    ) -> Result<bool, SrsError> {
+        let offset = match header.section(id) {
+            Some(section) => section.offset,
+            None => return Ok(false),
+        };
+        reader.seek(io::SeekFrom::Start(offset))?;
+        for k in 0..=max_k {
+            let g_lagrange = reorder_lagrange(read_points(reader, 1 << k)?, k);
+            if !arithmetic::same_lagrange(&powers[..1 << k], &g_lagrange) {
+                return Err(SrsError::ValidationFailed("lagrange"));
+            }
+        }
+        Ok(true)
     }
 
     pub fn read_g2s<M: MultiMillerLoop, R: io::Read + io::Seek, const IN_PLACE: bool>(
         reader: &mut R,
         n: usize,
-    ) -> Vec<M::G2Affine> {
+    ) -> Result<Vec<M::G2Affine>, SrsError> {
         if !IN_PLACE {
-            seek_g2_offset::<M, _>(reader);
+            seek_g2_offset::<M, _>(reader)?;
         }
+        read_g2_points::<M, _>(reader, n, "tau_g2")
+    }
 
+    fn read_g2_points<M: MultiMillerLoop, R: io::Read>(
+        reader: &mut R,
+        n: usize,
+        section: &'static str,
+    ) -> Result<Vec<M::G2Affine>, SrsError> {
         let mut reprs =
             vec![[<<M::G2Affine as CurveAffine>::Base as PrimeField>::Repr::default(); 2]; n];
         for repr in reprs.iter_mut() {
-            reader.read_exact(repr[0].as_mut()).unwrap();
-            reader.read_exact(repr[1].as_mut()).unwrap();
+            reader.read_exact(repr[0].as_mut())?;
+            reader.read_exact(repr[1].as_mut())?;
         }
 
         let g1_base_size = field_repr_size::<<M::G1Affine as CurveAffine>::Base>();
         let mont_r_inv = mont_r::<<M::G1Affine as CurveAffine>::Base>()
             .invert()
             .unwrap();
-        let mut points = vec![M::G2Affine::default(); n];
-        parallelize(&mut points, |points, start| {
-            for (i, point) in points.iter_mut().enumerate() {
-                let reprs = reprs[start + i].map(|mut repr| {
-                    let mut g1_base_reprs =
-                        [<<M::G1Affine as CurveAffine>::Base as PrimeField>::Repr::default(); 2];
-                    g1_base_reprs[0]
-                        .as_mut()
-                        .copy_from_slice(&repr.as_ref()[..g1_base_size]);
-                    g1_base_reprs[1]
-                        .as_mut()
-                        .copy_from_slice(&repr.as_ref()[g1_base_size..]);
-                    let g1_bases = g1_base_reprs.map(|g1_base_repr| {
-                        <M::G1Affine as CurveAffine>::Base::from_repr(g1_base_repr).unwrap()
-                            * mont_r_inv
-                    });
-                    repr.as_mut()[..g1_base_size].copy_from_slice(g1_bases[0].to_repr().as_ref());
-                    repr.as_mut()[g1_base_size..].copy_from_slice(g1_bases[1].to_repr().as_ref());
-                    repr
+        decode_ec_points(&reprs, |index, reprs| {
+            if is_zeros(reprs) {
+                return Ok(M::G2Affine::identity());
+            }
+            let [x, y] = reprs.map(|mut repr| {
+                let mut g1_base_reprs =
+                    [<<M::G1Affine as CurveAffine>::Base as PrimeField>::Repr::default(); 2];
+                g1_base_reprs[0]
+                    .as_mut()
+                    .copy_from_slice(&repr.as_ref()[..g1_base_size]);
+                g1_base_reprs[1]
+                    .as_mut()
+                    .copy_from_slice(&repr.as_ref()[g1_base_size..]);
+                let [c0, c1] = g1_base_reprs.map(|g1_base_repr| {
+                    Option::from(<M::G1Affine as CurveAffine>::Base::from_repr(g1_base_repr))
+                        .map(|base: <M::G1Affine as CurveAffine>::Base| base * mont_r_inv)
                 });
-                let [x, y] =
-                    reprs.map(|repr| <M::G2Affine as CurveAffine>::Base::from_repr(repr).unwrap());
-                *point = M::G2Affine::from_xy(x, y).unwrap();
+                let (c0, c1) = c0.zip(c1)?;
+                repr.as_mut()[..g1_base_size].copy_from_slice(c0.to_repr().as_ref());
+                repr.as_mut()[g1_base_size..].copy_from_slice(c1.to_repr().as_ref());
+                Option::from(<M::G2Affine as CurveAffine>::Base::from_repr(repr))
+            });
+            let (x, y) = x
+                .zip(y)
+                .ok_or(SrsError::InvalidPointEncoding { index, section })?;
+            Option::from(M::G2Affine::from_xy(x, y)).ok_or(SrsError::NotOnCurve { index, section })
+        })
+    }
+
+    /// Powers of tau file of snarkjs.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct SnarkJs;
+
+    impl<M: MultiMillerLoop> SrsReader<M> for SnarkJs {
+        fn read_k<R: io::Read + io::Seek>(&self, reader: &mut R) -> Result<u32, SrsError> {
+            read_k::<M, _>(reader)
+        }
+
+        fn read_g1s<R: io::Read + io::Seek>(
+            &self,
+            reader: &mut R,
+            range: Range<usize>,
+        ) -> Result<Vec<M::G1Affine>, SrsError> {
+            let g1_size = 2 * field_repr_size::<<M::G1Affine as CurveAffine>::Base>();
+            let offset = read_g1_offset::<M, _>(reader)? + (range.start * g1_size) as u64;
+            reader.seek(io::SeekFrom::Start(offset))?;
+            read_g1s::<M, _, true>(reader, range.len())
+        }
+
+        fn read_g1s_lagrange<R: io::Read + io::Seek>(
+            &self,
+            reader: &mut R,
+            k: u32,
+        ) -> Result<Option<Vec<M::G1Affine>>, SrsError> {
+            read_g1s_lagrange::<M, _>(reader, k)
+        }
+
+        fn read_g2s<R: io::Read + io::Seek>(
+            &self,
+            reader: &mut R,
+        ) -> Result<[M::G2Affine; 2], SrsError> {
+            Ok(read_g2s::<M, _, false>(reader, 2)?.try_into().unwrap())
+        }
+    }
+
+    impl<M: MultiMillerLoop> SrsWriter<M> for SnarkJs {
+        fn write<W: io::Write>(
+            &self,
+            writer: &mut W,
+            k: u32,
+            g: &[M::G1Affine],
+            _: &[M::G1Affine],
+            g2s: [M::G2Affine; 2],
+        ) -> Result<(), SrsError> {
+            write::<M, _>(writer, k, g, &g2s)
+        }
+    }
+}
+
+/// Point encoding of https://github.com/zkcrypto/pairing, which is used by
+/// ceremonies on BLS12-381 like Zcash, Filecoin and Ethereum, and its variant
+/// of https://github.com/matter-labs/pairing used by ceremonies on BN254 like
+/// Perpetual Powers of Tau.
+///
+/// Points are big-endian with flags in the most significant bits of the
+/// first byte. When the base field leaves the 3 most significant bits unused
+/// like BLS12-381, they are the compression, infinity and sign flags, where
+/// the sign flag is set if `y` is the greatest root. Otherwise like BN254,
+/// only the 2 most significant bits are flags, where the infinity flag is the
+/// same, and the compression flag is only used in compressed encoding to
+/// mean `y` is the greatest root.
+pub mod zcash {
+    use crate::util::{field_repr_size, Compression};
+    use halo2_curves::{
+        group::ff::{Field, PrimeField},
+        Coordinates, CurveAffine,
+    };
+
+    pub const COMPRESSION_FLAG: u8 = 0b1000_0000;
+    pub const INFINITY_FLAG: u8 = 0b0100_0000;
+    pub const SIGN_FLAG: u8 = 0b0010_0000;
+
+    pub fn ec_point_size<C: CurveAffine>(compression: Compression) -> usize {
+        match compression {
+            Compression::Compressed => field_repr_size::<C::Base>(),
+            Compression::Uncompressed => 2 * field_repr_size::<C::Base>(),
+        }
+    }
+
+    /// Returns whether the sign flag is available, which is when a repr with
+    /// only the sign flag bit set is not a valid field element.
+    pub fn has_sign_flag<F: PrimeField>() -> bool {
+        let mut repr = F::Repr::default();
+        *repr.as_mut().last_mut().unwrap() = SIGN_FLAG;
+        bool::from(F::from_repr(repr).is_none())
+    }
+
+    /// Returns mask of the flags in the first byte.
+    fn flags_mask<F: PrimeField>() -> u8 {
+        if has_sign_flag::<F>() {
+            COMPRESSION_FLAG | INFINITY_FLAG | SIGN_FLAG
+        } else {
+            COMPRESSION_FLAG | INFINITY_FLAG
+        }
+    }
+
+    /// Copies big-endian `bytes` into a little-endian repr with `mask`
+    /// cleared from the first byte.
+    fn to_repr<F: PrimeField>(bytes: &[u8], mask: u8) -> F::Repr {
+        let mut repr = F::Repr::default();
+        repr.as_mut().copy_from_slice(bytes);
+        repr.as_mut()[0] &= !mask;
+        repr.as_mut().reverse();
+        repr
+    }
+
+    fn decode_identity<C: CurveAffine>(mask: u8, bytes: &[u8]) -> Option<C> {
+        let is_zero = bytes[0] & !mask == 0 && bytes[1..].iter().all(|byte| *byte == 0);
+        is_zero.then(C::identity)
+    }
+
+    /// Decodes a point from big-endian compressed encoding.
+    pub fn decode_compressed<C: CurveAffine>(bytes: &[u8]) -> Option<C> {
+        if bytes.len() != ec_point_size::<C>(Compression::Compressed) {
+            return None;
+        }
+        let mask = flags_mask::<C::Base>();
+        let flags = bytes[0] & mask;
+        let greatest = if mask & SIGN_FLAG != 0 {
+            if flags & COMPRESSION_FLAG == 0 {
+                return None;
+            }
+            flags & SIGN_FLAG != 0
+        } else {
+            flags & COMPRESSION_FLAG != 0
+        };
+        if flags & INFINITY_FLAG != 0 {
+            return if greatest {
+                None
+            } else {
+                decode_identity(mask, bytes)
+            };
+        }
+
+        let x = Option::<C::Base>::from(C::Base::from_repr(to_repr::<C::Base>(bytes, mask)))?;
+        let y = Option::<C::Base>::from((x.square() * x + C::a() * x + C::b()).sqrt())?;
+        let y = if (y > -y) == greatest { y } else { -y };
+        Option::from(C::from_xy(x, y))
+    }
+
+    /// Decodes a point from big-endian `x` followed by big-endian `y`, with
+    /// only the infinity flag allowed.
+    pub fn decode_uncompressed<C: CurveAffine>(bytes: &[u8]) -> Option<C> {
+        if bytes.len() != ec_point_size::<C>(Compression::Uncompressed) {
+            return None;
+        }
+        let mask = flags_mask::<C::Base>();
+        let flags = bytes[0] & mask;
+        if flags & !INFINITY_FLAG != 0 {
+            return None;
+        }
+        if flags & INFINITY_FLAG != 0 {
+            return decode_identity(mask, bytes);
+        }
+
+        let (x, y) = bytes.split_at(bytes.len() / 2);
+        let x = Option::<C::Base>::from(C::Base::from_repr(to_repr::<C::Base>(x, mask)))?;
+        let y = Option::<C::Base>::from(C::Base::from_repr(to_repr::<C::Base>(y, 0)))?;
+        Option::from(C::from_xy(x, y))
+    }
+
+    /// Encodes a point into big-endian `x` with flags, or zeros with the
+    /// infinity flag for identity.
+    pub fn encode_compressed<C: CurveAffine>(point: &C) -> Vec<u8> {
+        let mut bytes = vec![0; ec_point_size::<C>(Compression::Compressed)];
+        let has_sign_flag = has_sign_flag::<C::Base>();
+        match Option::<Coordinates<C>>::from(point.coordinates()) {
+            Some(coordinates) => {
+                bytes.copy_from_slice(coordinates.x().to_repr().as_ref());
+                bytes.reverse();
+                let y = *coordinates.y();
+                if y > -y {
+                    bytes[0] |= if has_sign_flag {
+                        SIGN_FLAG
+                    } else {
+                        COMPRESSION_FLAG
+                    };
+                }
+            }
+            None => bytes[0] = INFINITY_FLAG,
+        }
+        if has_sign_flag {
+            bytes[0] |= COMPRESSION_FLAG;
+        }
+        bytes
+    }
+
+    /// Encodes a point into big-endian `x` followed by big-endian `y`, or
+    /// zeros with the infinity flag for identity.
+    pub fn encode_uncompressed<C: CurveAffine>(point: &C) -> Vec<u8> {
+        let mut bytes = vec![0; ec_point_size::<C>(Compression::Uncompressed)];
+        match Option::<Coordinates<C>>::from(point.coordinates()) {
+            Some(coordinates) => {
+                let (x, y) = bytes.split_at_mut(field_repr_size::<C::Base>());
+                for (bytes, fe) in [(x, coordinates.x()), (y, coordinates.y())] {
+                    bytes.copy_from_slice(fe.to_repr().as_ref());
+                    bytes.reverse();
+                }
             }
-        });
-        points
+            None => bytes[0] = INFINITY_FLAG,
+        }
+        bytes
+    }
+
+    pub fn decode<C: CurveAffine>(bytes: &[u8], compression: Compression) -> Option<C> {
+        match compression {
+            Compression::Compressed => decode_compressed(bytes),
+            Compression::Uncompressed => decode_uncompressed(bytes),
+        }
+    }
+
+    pub fn encode<C: CurveAffine>(point: &C, compression: Compression) -> Vec<u8> {
+        match compression {
+            Compression::Compressed => encode_compressed(point),
+            Compression::Uncompressed => encode_uncompressed(point),
+        }
     }
 }
@@ -0,0 +1,138 @@
+use crate::{
+    arithmetic::g_to_lagrange,
+    check_desired_k,
+    util::{ec_point_raw_size, pse},
+    Srs, SrsError,
+};
+use halo2_curves::{pairing::MultiMillerLoop, serde::SerdeObject};
+use memmap2::Mmap;
+use std::{fs::File, marker::PhantomData, ops::Range, path::Path};
+
+/// Memory-mapped view of a file in [`SrsFormat::PseRaw`], which decodes
+/// points on demand instead of loading the whole file.
+///
+/// Accessors of single point skip the on curve check, so the file is expected
+/// to be trusted or checked by [`SrsView::to_srs`] once.
+///
+/// [`SrsFormat::PseRaw`]: crate::SrsFormat::PseRaw
+pub struct SrsView<M: MultiMillerLoop> {
+    k: u32,
+    mmap: Mmap,
+    _marker: PhantomData<M>,
+}
+
+impl<M: MultiMillerLoop> SrsView<M>
+where
+    M::G1Affine: SerdeObject,
+    M::G2Affine: SerdeObject,
+{
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SrsError> {
+        let file = File::open(path)?;
+        // Safety: The file is only read, and is expected not to be modified
+        // by others while mapped.
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        let k = match mmap.get(..pse::G1_OFFSET as usize) {
+            Some(bytes) => u32::from_le_bytes(bytes.try_into().unwrap()),
+            None => {
+                return Err(SrsError::UnexpectedHeader(
+                    "File is too short to contain k".to_string(),
+                ))
+            }
+        };
+        let expected_len = 1usize
+            .checked_shl(k)
+            .and_then(|n| n.checked_mul(2 * ec_point_raw_size::<M::G1Affine>()))
+            .and_then(|g1s_len| g1s_len.checked_add(2 * ec_point_raw_size::<M::G2Affine>()))
+            .and_then(|len| len.checked_add(pse::G1_OFFSET as usize));
+        if expected_len != Some(mmap.len()) {
+            return Err(SrsError::UnexpectedHeader(format!(
+                "Unexpected file length {} for k = {k}",
+                mmap.len()
+            )));
+        }
+
+        Ok(Self {
+            k,
+            mmap,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+
+    fn g1_bytes(&self, range: Range<usize>, lagrange: bool) -> &[u8] {
+        assert!(range.end <= 1 << self.k);
+        let size = ec_point_raw_size::<M::G1Affine>();
+        let offset = pse::G1_OFFSET as usize + if lagrange { size << self.k } else { 0 };
+        &self.mmap[offset + range.start * size..offset + range.end * size]
+    }
+
+    fn g2_bytes(&self, index: usize) -> &[u8] {
+        let size = ec_point_raw_size::<M::G2Affine>();
+        let offset = pse::G1_OFFSET as usize + 2 * (ec_point_raw_size::<M::G1Affine>() << self.k);
+        &self.mmap[offset + index * size..offset + (index + 1) * size]
+    }
+
+    pub fn g(&self, index: usize) -> M::G1Affine {
+        M::G1Affine::from_raw_bytes_unchecked(self.g1_bytes(index..index + 1, false))
+    }
+
+    pub fn g_lagrange(&self, index: usize) -> M::G1Affine {
+        M::G1Affine::from_raw_bytes_unchecked(self.g1_bytes(index..index + 1, true))
+    }
+
+    pub fn g2(&self) -> M::G2Affine {
+        M::G2Affine::from_raw_bytes_unchecked(self.g2_bytes(0))
+    }
+
+    pub fn s_g2(&self) -> M::G2Affine {
+        M::G2Affine::from_raw_bytes_unchecked(self.g2_bytes(1))
+    }
+
+    /// Decodes `g` in `range` in parallel.
+    pub fn g_range<const CHECKED: bool>(
+        &self,
+        range: Range<usize>,
+    ) -> Result<Vec<M::G1Affine>, SrsError> {
+        pse::decode_raw_ec_points::<_, CHECKED>(self.g1_bytes(range, false), "g1")
+    }
+
+    /// Decodes `g_lagrange` in `range` in parallel.
+    pub fn g_lagrange_range<const CHECKED: bool>(
+        &self,
+        range: Range<usize>,
+    ) -> Result<Vec<M::G1Affine>, SrsError> {
+        pse::decode_raw_ec_points::<_, CHECKED>(self.g1_bytes(range, true), "g1")
+    }
+
+    /// Decodes an owned [`Srs`] with `desired_k`, which only touches the part
+    /// of the file needed.
+    pub fn to_srs<const CHECKED: bool>(&self, desired_k: u32) -> Result<Srs<M>, SrsError> {
+        check_desired_k(desired_k, self.k)?;
+
+        let n = 1 << desired_k;
+        let g = self.g_range::<CHECKED>(0..n)?;
+        let g_lagrange = if desired_k == self.k {
+            self.g_lagrange_range::<CHECKED>(0..n)?
+        } else {
+            g_to_lagrange(&g, desired_k)
+        };
+        let [g2, s_g2]: [_; 2] = pse::decode_raw_ec_points::<_, CHECKED>(
+            &self.mmap[self.mmap.len() - 2 * ec_point_raw_size::<M::G2Affine>()..],
+            "g2",
+        )?
+        .try_into()
+        .unwrap();
+
+        Ok(Srs {
+            k: desired_k,
+            g,
+            g_lagrange,
+            g2,
+            s_g2,
+        })
+    }
+}
@@ -3,7 +3,7 @@ use halo2_kzg_srs::{
     arithmetic::same_ratio,
     util::{
         ec_point_raw_size, ec_point_repr_size, field_repr_size, perpetual_powers_of_tau, pse,
-        snarkjs,
+        snarkjs, Compression,
     },
 };
 use hyper::{body::HttpBody, Body, Client, Request};
@@ -38,22 +38,26 @@ async fn fetch_pse_g1s<const RAW: bool>(source: &str, k: usize, max_k: usize) ->
             ec_point_repr_size::<G1Affine>()
         } << k.min(max_k));
     let mut reader = Cursor::new(fetch(&uri, 0, length).await);
-    pse::read_g1s::<Bn256, _, RAW, false>(&mut reader, k.min(max_k))
+    pse::read_g1s::<Bn256, _, RAW, false>(&mut reader, k.min(max_k)).unwrap()
 }
 
 async fn fetch_hermez_g2s() -> [G2Affine; 2] {
     let uri = "https://hermez.s3-eu-west-1.amazonaws.com/powersOfTau28_hez_final_08.ptau";
-    let header_size = {
-        let mut reader = Cursor::new(fetch(uri, 0, snarkjs::HEADER_OFFSET as usize).await);
-        snarkjs::read_header_size(&mut reader)
-    };
-    let g2_offset = {
+    // Walk through the section table without fetching the section data.
+    let mut offset = snarkjs::SECTIONS_OFFSET;
+    let g2_offset = loop {
         let mut reader =
-            Cursor::new(fetch(uri, 0, (snarkjs::HEADER_OFFSET + header_size) as usize).await);
-        snarkjs::read_g2_offset::<Bn256, _>(&mut reader)
+            Cursor::new(fetch(uri, offset as usize, snarkjs::SECTION_HEADER_SIZE as usize).await);
+        let (id, size) = snarkjs::read_section_header(&mut reader).unwrap();
+        offset += snarkjs::SECTION_HEADER_SIZE;
+        if id == snarkjs::TAU_G2_SECTION_ID {
+            break offset;
+        }
+        offset += size;
     };
     let mut reader = Cursor::new(fetch(uri, g2_offset as usize, 8 * field_repr_size::<Fq>()).await);
     snarkjs::read_g2s::<Bn256, _, true>(&mut reader, 2)
+        .unwrap()
         .try_into()
         .unwrap()
 }
@@ -61,9 +65,11 @@ async fn fetch_hermez_g2s() -> [G2Affine; 2] {
 async fn fetch_perpetual_powers_of_tau_g2() -> [G2Affine; 2] {
     const K: u32 = 28;
     let uri = "https://ppot.blob.core.windows.net/public/response_0071_edward";
-    let g2_offset = perpetual_powers_of_tau::g2_offset::<Bn256>(K) as usize;
+    let g2_offset =
+        perpetual_powers_of_tau::g2_offset::<Bn256>(K, Compression::Compressed) as usize;
     let mut reader = Cursor::new(fetch(uri, g2_offset, 2 * ec_point_repr_size::<G2Affine>()).await);
-    perpetual_powers_of_tau::read_g2s::<Bn256, _, true>(&mut reader, K, 2)
+    perpetual_powers_of_tau::read_g2s::<Bn256, _, true>(&mut reader, K, 2, Compression::Compressed)
+        .unwrap()
         .try_into()
         .unwrap()
 }